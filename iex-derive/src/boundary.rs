@@ -0,0 +1,78 @@
+use crate::{define_try_macro, infer_impl_trait, result_types, returns, ReplaceTry};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_quote, parse_quote_spanned, spanned::Spanned, visit_mut::VisitMut, Block, ExprClosure,
+    Ident, ItemFn, ReturnType,
+};
+
+pub(crate) fn transform_item_fn(input: ItemFn) -> TokenStream {
+    if let Some(constness) = input.sig.constness {
+        return quote_spanned! {
+            constness.span() => compile_error!("#[iex] does not support const functions");
+        };
+    }
+    if let Some(asyncness) = input.sig.asyncness {
+        return quote_spanned! {
+            asyncness.span() => compile_error!("#[iex(boundary)] does not support async functions");
+        };
+    }
+
+    let input_span = input.span();
+
+    let result_type = match input.sig.output {
+        ReturnType::Default => parse_quote! { () },
+        ReturnType::Type(_, ref result_type) => result_type.clone(),
+    };
+    let (output_type, error_type) = result_types(&result_type);
+    let body_output_type = infer_impl_trait(&output_type);
+    let body_error_type = infer_impl_trait(&error_type);
+
+    let mut closure_block = input.block;
+    let mut replace_try = ReplaceTry {
+        errors: darling::Error::accumulator(),
+    };
+    replace_try.visit_block_mut(&mut closure_block);
+    if let Err(err) = replace_try.errors.finish() {
+        return err.write_errors();
+    }
+    returns::replace_returns_in_block(&mut closure_block);
+    closure_block.stmts.insert(0, define_try_macro());
+
+    let mut closure: ExprClosure = parse_quote_spanned! {
+        Span::mixed_site() =>
+        move |marker: ::iex::imp::Marker<#body_error_type>| -> ::core::result::Result<#body_output_type, #body_error_type> {
+            #closure_block
+        }
+    };
+    closure.attrs.push(parse_quote! { #[inline(always)] });
+
+    let name = &input.sig.ident;
+    let marker: Ident = parse_quote_spanned! { Span::mixed_site() => marker };
+
+    // The signature is kept as is. The body is compiled like that of an #[iex] function, and its
+    // outcome is converted back to the declared type once, here.
+    let block: Block = parse_quote_spanned! {
+        // This span is required for dead code diagnostic
+        input_span =>
+        {
+            #[allow(unused_imports)]
+            use ::iex::imp::_IexForward;
+            // We need { .. } to support the #[inline] attribute on the closure
+            #[allow(unused_mut)]
+            let mut #name = { #closure };
+            ::iex::imp::FromResult::from_result(::iex::Outcome::into_result(::iex::imp::IexResult(
+                move |#marker: ::iex::imp::Marker<#body_error_type>| {
+                    ::iex::Outcome::get_value_or_panic(#name(#marker), #marker)
+                },
+                ::core::marker::PhantomData,
+            )))
+        }
+    };
+
+    let output = ItemFn {
+        block: Box::new(block),
+        ..input
+    };
+    quote! { #output }
+}
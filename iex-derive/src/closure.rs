@@ -0,0 +1,98 @@
+use crate::{define_try_macro, returns, ReplaceTry};
+use proc_macro2::{Span, TokenStream};
+use quote::quote_spanned;
+use syn::{
+    parse_quote, parse_quote_spanned, spanned::Spanned, visit_mut::VisitMut, Expr, ExprClosure,
+    Ident, Pat, PatType, ReturnType, Stmt, Type,
+};
+
+pub(crate) fn transform_closure(input: ExprClosure) -> TokenStream {
+    if let Some(constness) = input.constness {
+        return quote_spanned! {
+            constness.span() => compile_error!("iex::closure! does not support const closures");
+        };
+    }
+    if let Some(asyncness) = input.asyncness {
+        return quote_spanned! {
+            asyncness.span() => compile_error!("iex::closure! does not support async closures");
+        };
+    }
+
+    let input_span = input.span();
+
+    let output_type: Type;
+    let error_type: Type;
+    match input.output {
+        ReturnType::Default => {
+            output_type = parse_quote! { _ };
+            error_type = parse_quote! { _ };
+        }
+        ReturnType::Type(_, ref result_type) => {
+            output_type = parse_quote! { <#result_type as ::iex::Outcome>::Output };
+            error_type = parse_quote! { <#result_type as ::iex::Outcome>::Error };
+        }
+    }
+
+    let mut closure_body = input.body;
+    let mut replace_try = ReplaceTry {
+        errors: darling::Error::accumulator(),
+    };
+    replace_try.visit_expr_mut(&mut closure_body);
+    if let Err(err) = replace_try.errors.finish() {
+        return err.write_errors();
+    }
+    returns::replace_returns_in_expr(&mut closure_body);
+    // Workaround false positive "useless { .. } around return value" warning.
+    let mut closure_body = match *closure_body {
+        Expr::Block(block) if block.attrs.is_empty() && block.label.is_none() => block.block.stmts,
+        expr => vec![Stmt::Expr(expr, None)],
+    };
+    closure_body.insert(0, define_try_macro());
+
+    let marker: Ident = parse_quote_spanned! { Span::mixed_site() => marker };
+    let body_ident: Ident = parse_quote_spanned! { Span::mixed_site() => body };
+    let closure_ident: Ident = parse_quote_spanned! { Span::mixed_site() => closure };
+
+    // The marker is passed as an argument rather than captured by a returned outcome, so that the
+    // return type does not depend on the lifetimes of the arguments. The body is wrapped in a
+    // non-move closure so that `return` works and that captures are only moved out when the body
+    // consumes them, which keeps the Fn/FnMut/FnOnce inference intact.
+    let mut inputs = input.inputs;
+    inputs.push(Pat::Type(PatType {
+        attrs: Vec::new(),
+        pat: Box::new(parse_quote! { #marker }),
+        colon_token: Default::default(),
+        ty: Box::new(parse_quote! { ::iex::imp::Marker<#error_type> }),
+    }));
+    let raw_closure = ExprClosure {
+        output: parse_quote! { -> #output_type },
+        inputs,
+        body: Box::new(parse_quote_spanned! {
+            Span::mixed_site() =>
+            {
+                // We need { .. } to support the #[inline] attribute on the closure
+                #[allow(unused_mut)]
+                let mut #body_ident = {
+                    #[inline(always)]
+                    || -> ::core::result::Result<#output_type, #error_type> {
+                        #(#closure_body)*
+                    }
+                };
+                ::iex::Outcome::get_value_or_panic(#body_ident(), #marker)
+            }
+        }),
+        ..input
+    };
+
+    quote_spanned! {
+        // This span is required for dead code diagnostic
+        input_span =>
+        {
+            #[allow(unused_imports)]
+            use ::iex::imp::_IexForward;
+            // We need { .. } to support the #[inline] attribute on the closure
+            let #closure_ident = { #raw_closure };
+            ::iex::IexClosure::new(#closure_ident)
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    define_try_macro, make_doc_fn,
+    object_safe::{self, NameElidedLifetimes},
+    returns, ReplaceSelf, ReplaceTry,
+};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, parse_quote_spanned, spanned::Spanned, visit_mut::VisitMut, Error, Expr,
+    ExprClosure, FnArg, GenericParam, Ident, ItemFn, Lifetime, LifetimeParam, Pat, Result,
+    ReturnType, Type,
+};
+
+// Collects the distinct lifetimes mentioned in argument types, to find out which lifetime an
+// elided lifetime in the return type refers to.
+struct CollectLifetimes(Vec<Lifetime>);
+
+impl VisitMut for CollectLifetimes {
+    fn visit_lifetime_mut(&mut self, node: &mut Lifetime) {
+        if node.ident != "static" && !self.0.contains(node) {
+            self.0.push(node.clone());
+        }
+    }
+}
+
+fn check_signature(input: &ItemFn) -> Result<()> {
+    if let Some(constness) = input.sig.constness {
+        return Err(Error::new(
+            constness.span(),
+            "#[iex] does not support const functions",
+        ));
+    }
+    if let Some(asyncness) = input.sig.asyncness {
+        return Err(Error::new(
+            asyncness.span(),
+            "#[iex(fn_ptr)] does not support async functions",
+        ));
+    }
+    for arg in &input.sig.inputs {
+        if let FnArg::Typed(arg) = arg {
+            if let Type::ImplTrait(ty) = &*arg.ty {
+                return Err(Error::new(
+                    ty.span(),
+                    "#[iex(fn_ptr)] functions cannot take `impl Trait` arguments",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn transform_item_fn(input: ItemFn) -> TokenStream {
+    if let Err(err) = check_signature(&input) {
+        return err.into_compile_error();
+    }
+
+    let input_span = input.span();
+
+    let result_type: Type = match input.sig.output {
+        ReturnType::Default => parse_quote! { () },
+        ReturnType::Type(_, ref result_type) => (**result_type).clone(),
+    };
+    let mut output_type: Type = parse_quote! { <#result_type as ::iex::Outcome>::Output };
+    let mut error_type: Type = parse_quote! { <#result_type as ::iex::Outcome>::Error };
+
+    // The arguments are stored in the outcome, so their types must be spelled out in the return
+    // type. Elided lifetimes are named for that purpose.
+    let mut sig = input.sig.clone();
+    let mut name_elided_lifetimes = NameElidedLifetimes {
+        lifetimes: Vec::new(),
+        output: None,
+    };
+    let mut arg_values: Vec<Expr> = Vec::new();
+    let mut arg_pats: Vec<Pat> = Vec::new();
+    let mut arg_types = Vec::new();
+    let mut block = input.block;
+    for (i, arg) in sig.inputs.iter_mut().enumerate() {
+        match arg {
+            FnArg::Receiver(receiver) => {
+                let mut ty = (*receiver.ty).clone();
+                name_elided_lifetimes.visit_type_mut(&mut ty);
+                if let (Some((_, lifetime)), Type::Reference(reference)) =
+                    (&mut receiver.reference, &ty)
+                {
+                    *lifetime = reference.lifetime.clone();
+                }
+                *receiver.ty = ty.clone();
+                // The body is moved to a closure, which cannot refer to `self`
+                ReplaceSelf.visit_block_mut(&mut block);
+                arg_values.push(parse_quote! { self });
+                let mutability = receiver.mutability;
+                arg_pats.push(parse_quote_spanned! { Span::mixed_site() => #mutability iex_self });
+                arg_types.push(ty);
+            }
+            FnArg::Typed(arg) => {
+                name_elided_lifetimes.visit_type_mut(&mut arg.ty);
+                let arg_name = format_ident!("arg{}", i, span = Span::mixed_site());
+                arg_values.push(parse_quote! { #arg_name });
+                arg_pats.push(std::mem::replace(&mut *arg.pat, parse_quote! { #arg_name }));
+                arg_types.push((*arg.ty).clone());
+            }
+        }
+    }
+    for lifetime in name_elided_lifetimes.lifetimes {
+        sig.generics
+            .params
+            .push(GenericParam::Lifetime(LifetimeParam::new(lifetime)));
+    }
+
+    // Elided lifetimes in the return type refer to the only lifetime in the arguments. If there
+    // are several, the original signature is invalid anyway.
+    let mut collect_lifetimes = CollectLifetimes(Vec::new());
+    for ty in &mut arg_types {
+        collect_lifetimes.visit_type_mut(ty);
+    }
+    if let [ref lifetime] = collect_lifetimes.0[..] {
+        let mut name_elided_lifetimes = NameElidedLifetimes {
+            lifetimes: Vec::new(),
+            output: Some(lifetime.clone()),
+        };
+        name_elided_lifetimes.visit_type_mut(&mut output_type);
+        name_elided_lifetimes.visit_type_mut(&mut error_type);
+    }
+
+    sig.output = parse_quote! {
+        -> ::iex::FnOutcome<(#(#arg_types,)*), #output_type, #error_type>
+    };
+
+    let mut replace_try = ReplaceTry {
+        errors: darling::Error::accumulator(),
+    };
+    replace_try.visit_block_mut(&mut block);
+    if let Err(err) = replace_try.errors.finish() {
+        return err.write_errors();
+    }
+    returns::replace_returns_in_block(&mut block);
+    block.stmts.insert(0, define_try_macro());
+
+    let mut closure: ExprClosure = parse_quote_spanned! {
+        Span::mixed_site() =>
+        move |marker: ::iex::imp::Marker<#error_type>| -> ::core::result::Result<#output_type, #error_type> #block
+    };
+    closure.attrs = input
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc") && !attr.path().is_ident("inline"))
+        .cloned()
+        .collect();
+    closure.attrs.insert(0, parse_quote! { #[inline(always)] });
+
+    let name = &input.sig.ident;
+    let marker: Ident = parse_quote_spanned! { Span::mixed_site() => marker };
+
+    // The body is a non-capturing closure, so that it can be coerced to a function pointer. The
+    // user's #[inline] attribute applies to it, as it is the function that is actually called.
+    let inline_attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("inline"));
+    let mut call: ExprClosure = parse_quote_spanned! {
+        Span::mixed_site() =>
+        |(#(#arg_pats,)*): (#(#arg_types,)*), #marker: ::iex::imp::Marker<#error_type>| {
+            #[allow(unused_imports)]
+            use ::iex::imp::_IexForward;
+            // We need { .. } to support the #[inline] attribute on the closure
+            #[allow(unused_mut)]
+            let mut #name = { #closure };
+            ::iex::Outcome::get_value_or_panic(#name(#marker), #marker)
+        }
+    };
+    call.attrs.extend(inline_attr.cloned());
+    let call_ident: Ident = parse_quote_spanned! { Span::mixed_site() => call };
+
+    // Doc comments must stay in the wrapper even without #[cfg(doc)] because rustc applies the
+    // missing_docs lint without cfg(doc).
+    let mut wrapper_attrs: Vec<_> = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .cloned()
+        .collect();
+    wrapper_attrs.push(parse_quote! { #[cfg(not(doc))] });
+    wrapper_attrs.push(parse_quote! { #[inline(always)] });
+
+    // A hidden lifetime parameter, as in `arg: Type` with `struct Type<'a>`, cannot be named, and
+    // the elided lifetime in the return type becomes ambiguous if there are other lifetimes.
+    let check_hidden_lifetimes = if collect_lifetimes.0.is_empty() {
+        None
+    } else {
+        Some(object_safe::check_hidden_lifetimes(&input.sig))
+    };
+
+    let wrapper_fn = ItemFn {
+        attrs: wrapper_attrs,
+        vis: input.vis.clone(),
+        sig,
+        block: parse_quote_spanned! {
+            // This span is required for dead code diagnostic
+            input_span =>
+            {
+                #check_hidden_lifetimes
+                // We need { .. } to support the #[inline] attribute on the closure
+                let #call_ident = { #call };
+                ::iex::imp::IexResult(
+                    ::iex::imp::FnCall::new((#(#arg_values,)*), #call_ident),
+                    ::core::marker::PhantomData,
+                )
+            }
+        },
+    };
+
+    let doc_fn = make_doc_fn(input.attrs, input.vis, input.sig);
+
+    quote! {
+        #wrapper_fn
+        #doc_fn
+    }
+}
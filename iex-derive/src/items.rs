@@ -0,0 +1,154 @@
+use crate::MacroArgs;
+use darling::{ast::NestedMeta, FromMeta};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{
+    parse_quote, spanned::Spanned, Attribute, Error, ImplItem, Item, ItemImpl, ItemMod, ItemTrait,
+    Meta, Result, ReturnType, Signature, TraitItem, Type,
+};
+
+fn returns_result(output: &ReturnType) -> bool {
+    let ReturnType::Type(_, ty) = output else {
+        return false;
+    };
+    let Type::Path(ty) = &**ty else {
+        return false;
+    };
+    // Matches Result<T, E>, io::Result<T>, anyhow::Result<T>, etc.
+    ty.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Result")
+}
+
+fn is_iex_attr(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "iex")
+}
+
+fn is_skip_attr(attr: &Attribute) -> bool {
+    MacroArgs::from_meta(&attr.meta).is_ok_and(|args| args.skip)
+}
+
+// Appends `captures = ".."` for each of `lifetimes` to the arguments of #[iex], unless #[iex]
+// generates a signature that captures all lifetimes by itself, as for async and object-safe
+// functions, or no `impl Outcome` at all.
+fn add_captures(args: TokenStream, sig: &Signature, lifetimes: &[String]) -> TokenStream {
+    let Some(parsed_args) = NestedMeta::parse_meta_list(args.clone())
+        .ok()
+        .and_then(|list| MacroArgs::from_list(&list).ok())
+    else {
+        // Reported by #[iex] itself
+        return args;
+    };
+    if lifetimes.is_empty()
+        || sig.asyncness.is_some()
+        || parsed_args.object_safe
+        || parsed_args.fn_ptr
+        || parsed_args.boundary
+    {
+        return args;
+    }
+    if args.is_empty() {
+        quote! { #(captures = #lifetimes),* }
+    } else {
+        quote! { #args, #(captures = #lifetimes),* }
+    }
+}
+
+// Adds #[iex] to a function unless it is marked with #[iex] or #[iex(skip)] explicitly. Const
+// functions are skipped, as #[iex] does not support them. `lifetimes` are the lifetimes of the
+// enclosing impl block, which the function may capture without mentioning them in its signature.
+fn mark_fn(attrs: &mut Vec<Attribute>, sig: &Signature, args: &TokenStream, lifetimes: &[String]) {
+    if attrs.iter().any(is_iex_attr) {
+        attrs.retain(|attr| !(is_iex_attr(attr) && is_skip_attr(attr)));
+        for attr in attrs.iter_mut().filter(|attr| is_iex_attr(attr)) {
+            let (path, args) = match &attr.meta {
+                Meta::Path(path) => (path, TokenStream::new()),
+                Meta::List(list) => (&list.path, list.tokens.clone()),
+                Meta::NameValue(_) => continue,
+            };
+            let args = add_captures(args, sig, lifetimes);
+            attr.meta = parse_quote! { #path(#args) };
+        }
+        return;
+    }
+    if sig.constness.is_none() && returns_result(&sig.output) {
+        let args = add_captures(args.clone(), sig, lifetimes);
+        // As if #[iex] was the outermost attribute
+        attrs.insert(0, parse_quote! { #[::iex::iex(#args)] });
+    }
+}
+
+fn transform_impl(args: &TokenStream, input: &mut ItemImpl) {
+    // Methods of trait impls capture all lifetimes like the trait methods, and additional bounds
+    // would not match the trait
+    let lifetimes: Vec<String> = if input.trait_.is_none() {
+        input
+            .generics
+            .lifetimes()
+            .map(|param| param.lifetime.to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    for item in &mut input.items {
+        if let ImplItem::Fn(item) = item {
+            mark_fn(&mut item.attrs, &item.sig, args, &lifetimes);
+        }
+    }
+}
+
+fn transform_trait(args: &TokenStream, input: &mut ItemTrait) {
+    for item in &mut input.items {
+        if let TraitItem::Fn(item) = item {
+            mark_fn(&mut item.attrs, &item.sig, args, &[]);
+        }
+    }
+}
+
+fn transform_mod(args: &TokenStream, input: &mut ItemMod) -> Result<()> {
+    let Some((_, ref mut items)) = input.content else {
+        return Err(Error::new(
+            input.span(),
+            "#[iex] can only be applied to inline modules",
+        ));
+    };
+    for item in items {
+        match item {
+            Item::Fn(item) => mark_fn(&mut item.attrs, &item.sig, args, &[]),
+            Item::Impl(item) => transform_impl(args, item),
+            Item::Trait(item) => transform_trait(args, item),
+            Item::Mod(item) => transform_mod(args, item)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn transform_item(args: TokenStream, mut input: Item) -> TokenStream {
+    let result = match input {
+        Item::Impl(ref mut input) => {
+            transform_impl(&args, input);
+            Ok(())
+        }
+        Item::Trait(ref mut input) => {
+            transform_trait(&args, input);
+            Ok(())
+        }
+        Item::Mod(ref mut input) => transform_mod(&args, input),
+        _ => unreachable!(),
+    };
+    match result {
+        Ok(()) => input.into_token_stream(),
+        Err(err) => {
+            let err = err.into_compile_error();
+            quote! {
+                #err
+                #input
+            }
+        }
+    }
+}
@@ -4,15 +4,32 @@ use quote::{quote, quote_spanned};
 use syn::{
     parse, parse_macro_input, parse_quote, parse_quote_spanned, parse_str,
     spanned::Spanned,
-    visit_mut::{visit_expr_mut, VisitMut},
-    Block, Expr, ExprClosure, ExprMethodCall, ExprTry, Ident, ImplItemFn, ItemFn, Lifetime, Macro,
-    ReturnType, Signature, Stmt, TraitItemFn, Type,
+    visit_mut::{self, visit_expr_mut, VisitMut},
+    Attribute, Block, Expr, ExprClosure, ExprMethodCall, ExprTry, GenericArgument, Ident,
+    ImplItemFn, Item, ItemFn, ItemMacro, Lifetime, Macro, PathArguments, ReturnType, Signature,
+    Stmt, TraitItemFn, Type, TypeImplTrait, Visibility,
 };
 
+mod boundary;
+mod closure;
+mod fn_ptr;
+mod items;
+mod object_safe;
+mod returns;
+mod tokens;
+
 #[derive(FromMeta)]
 struct MacroArgs {
     #[darling(multiple)]
     captures: Vec<String>,
+    #[darling(default)]
+    object_safe: bool,
+    #[darling(default)]
+    fn_ptr: bool,
+    #[darling(default)]
+    boundary: bool,
+    #[darling(default)]
+    skip: bool,
 }
 
 #[derive(FromAttributes, Debug)]
@@ -57,6 +74,105 @@ impl VisitMut for ReplaceSelf {
     fn visit_trait_item_fn_mut(&mut self, _node: &mut TraitItemFn) {}
 }
 
+// Whether the type mentions `impl Trait`.
+struct ContainsImplTrait(bool);
+
+impl VisitMut for ContainsImplTrait {
+    fn visit_type_impl_trait_mut(&mut self, _node: &mut TypeImplTrait) {
+        self.0 = true;
+    }
+}
+
+fn contains_impl_trait(ty: &Type) -> bool {
+    let mut visitor = ContainsImplTrait(false);
+    visitor.visit_type_mut(&mut ty.clone());
+    visitor.0
+}
+
+// Replaces `impl Trait` with `_`, so that the type can be used inside the function body.
+struct InferImplTrait;
+
+impl VisitMut for InferImplTrait {
+    fn visit_type_mut(&mut self, node: &mut Type) {
+        if let Type::ImplTrait(_) = node {
+            *node = parse_quote! { _ };
+        } else {
+            visit_mut::visit_type_mut(self, node);
+        }
+    }
+}
+
+fn infer_impl_trait(ty: &Type) -> Type {
+    let mut ty = ty.clone();
+    InferImplTrait.visit_type_mut(&mut ty);
+    ty
+}
+
+// Exposes the `Try` implementation of the returned outcome with the `nightly` feature. Nested
+// `impl Trait` is not allowed in generic arguments, so outcomes with `impl Trait` types don't get
+// native `?` support.
+fn native_try_bound(output_type: &Type, error_type: &Type) -> TokenStream {
+    if cfg!(not(feature = "nightly"))
+        || contains_impl_trait(output_type)
+        || contains_impl_trait(error_type)
+    {
+        TokenStream::new()
+    } else {
+        quote! { + ::iex::imp::NativeTry<#output_type, #error_type> }
+    }
+}
+
+// Splits the declared result type into the success type and the error type. `impl Trait` is not
+// allowed in `<#result_type as Outcome>::Output`, so in that case `Result<T, E>` is split
+// syntactically, and the error type of an alias like `io::Result<T>` is taken from `io::Result<()>`.
+fn result_types(result_type: &Type) -> (Type, Type) {
+    let projections = || {
+        (
+            parse_quote! { <#result_type as ::iex::Outcome>::Output },
+            parse_quote! { <#result_type as ::iex::Outcome>::Error },
+        )
+    };
+    if !contains_impl_trait(result_type) {
+        return projections();
+    }
+    let Type::Path(path) = result_type else {
+        return projections();
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return projections();
+    };
+    let PathArguments::AngleBracketed(ref args) = segment.arguments else {
+        return projections();
+    };
+    let types: Vec<&Type> = args
+        .args
+        .iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect();
+    match types[..] {
+        [output_type, error_type] => (output_type.clone(), error_type.clone()),
+        [output_type] => {
+            let mut unit_result = path.clone();
+            let segment = unit_result.path.segments.last_mut().unwrap();
+            if let PathArguments::AngleBracketed(ref mut args) = segment.arguments {
+                for arg in &mut args.args {
+                    if let GenericArgument::Type(ty) = arg {
+                        *ty = parse_quote! { () };
+                    }
+                }
+            }
+            (
+                output_type.clone(),
+                parse_quote! { <#unit_result as ::iex::Outcome>::Error },
+            )
+        }
+        _ => projections(),
+    }
+}
+
 fn generate_map_inspect_err(
     outcome: &mut Expr,
     closure: &mut Expr,
@@ -157,6 +273,26 @@ impl VisitMut for ReplaceTry {
         }
         visit_expr_mut(self, node);
     }
+    fn visit_macro_mut(&mut self, node: &mut Macro) {
+        // `?` in other macros, including local macro_rules! definitions, is only rewritten if they
+        // opt in with #[iex::try_macro]. iex::closure! and try_block! handle `?` in their bodies
+        // themselves.
+        if !node
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| tokens::EXPR_MACROS.contains(&&*segment.ident.to_string()))
+        {
+            return;
+        }
+        // Best-effort
+        node.tokens = tokens::replace_try(node.tokens.clone(), &mut |expr| {
+            quote_spanned! {
+                Span::mixed_site() =>
+                (marker, ::core::mem::ManuallyDrop::new(#expr))._iex_forward()
+            }
+        });
+    }
     // Don't recurse into other functions or closures
     fn visit_item_fn_mut(&mut self, _node: &mut ItemFn) {}
     fn visit_impl_item_fn_mut(&mut self, _node: &mut ImplItemFn) {}
@@ -164,6 +300,25 @@ impl VisitMut for ReplaceTry {
     fn visit_expr_closure_mut(&mut self, _node: &mut ExprClosure) {}
 }
 
+fn try_macro_call(expr: TokenStream) -> TokenStream {
+    quote_spanned! { Span::mixed_site() => __iex_try!(#expr) }
+}
+
+// Defines the macro that `?` is rewritten to in macros marked with #[iex::try_macro]. Macros cannot
+// refer to the local `marker` variable directly due to hygiene, so this macro is defined in every
+// #[iex] body and shadows the fallback that applies outside #[iex] bodies.
+fn define_try_macro() -> Stmt {
+    parse_quote_spanned! {
+        Span::mixed_site() =>
+        #[allow(unused_macros)]
+        macro_rules! __iex_try {
+            ($e:expr) => {
+                (marker, ::core::mem::ManuallyDrop::new($e))._iex_forward()
+            };
+        }
+    }
+}
+
 fn transform_trait_item_fn(captures: Vec<Lifetime>, input: TraitItemFn) -> proc_macro::TokenStream {
     // If default is Some(..), the input should have already been parsed as an ItemFn.
     assert!(input.default.is_none());
@@ -172,13 +327,14 @@ fn transform_trait_item_fn(captures: Vec<Lifetime>, input: TraitItemFn) -> proc_
         ReturnType::Default => parse_quote! { () },
         ReturnType::Type(_, ref result_type) => result_type.clone(),
     };
-    let output_type: Type = parse_quote! { <#result_type as ::iex::Outcome>::Output };
-    let error_type: Type = parse_quote! { <#result_type as ::iex::Outcome>::Error };
+    let (output_type, error_type) = result_types(&result_type);
+    let native_try = native_try_bound(&output_type, &error_type);
     let to_impl_outcome: ReturnType = parse_quote! {
         -> impl ::iex::Outcome<
             Output = #output_type,
             Error = #error_type,
-        > #(+ ::iex::imp::fix_hidden_lifetime_bug::Captures<#captures>)*
+        > #native_try
+        #(+ ::iex::imp::fix_hidden_lifetime_bug::Captures<#captures>)*
     };
 
     // We used to add '#result_type: ::iex::Outcome' to the 'where' condition. This is wrong for the
@@ -207,7 +363,17 @@ fn transform_trait_item_fn(captures: Vec<Lifetime>, input: TraitItemFn) -> proc_
         semi_token: input.semi_token,
     };
 
-    let name = &input.sig.ident;
+    let doc_fn = make_trait_doc_fn(input.attrs, input.sig);
+
+    quote! {
+        #wrapper_fn
+        #doc_fn
+    }
+    .into()
+}
+
+fn make_trait_doc_fn(attrs: Vec<Attribute>, sig: Signature) -> TraitItemFn {
+    let name = &sig.ident;
 
     let doc = format!(
         "
@@ -219,21 +385,15 @@ fn transform_trait_item_fn(captures: Vec<Lifetime>, input: TraitItemFn) -> proc_
         }}
     </style>"
     );
-    let mut doc_attrs = input.attrs;
+    let mut doc_attrs = attrs;
     doc_attrs.insert(0, parse_quote! { #[cfg(doc)] });
     doc_attrs.push(parse_quote! { #[doc = #doc] });
-    let doc_fn = TraitItemFn {
+    TraitItemFn {
         attrs: doc_attrs,
-        sig: input.sig,
+        sig,
         default: None,
-        semi_token: input.semi_token,
-    };
-
-    quote! {
-        #wrapper_fn
-        #doc_fn
+        semi_token: Some(Default::default()),
     }
-    .into()
 }
 
 fn transform_item_fn(captures: Vec<Lifetime>, input: ItemFn) -> proc_macro::TokenStream {
@@ -245,24 +405,28 @@ fn transform_item_fn(captures: Vec<Lifetime>, input: ItemFn) -> proc_macro::Toke
         }
         .into();
     }
-    if let Some(asyncness) = input.sig.asyncness {
-        return quote_spanned! {
-            asyncness.span() => compile_error!("#[iex] does not support async functions");
-        }
-        .into();
+    if input.sig.asyncness.is_some() {
+        return transform_async_item_fn(captures, input);
     }
 
     let result_type = match input.sig.output {
         ReturnType::Default => parse_quote! { () },
         ReturnType::Type(_, ref result_type) => result_type.clone(),
     };
-    let output_type: Type = parse_quote! { <#result_type as ::iex::Outcome>::Output };
-    let error_type: Type = parse_quote! { <#result_type as ::iex::Outcome>::Error };
+    let (output_type, error_type) = result_types(&result_type);
+    // The types of the body, where `impl Trait` is not allowed
+    let body_output_type = infer_impl_trait(&output_type);
+    let body_error_type = infer_impl_trait(&error_type);
+    // Lifetimes used in the arguments are captured by #[fix_hidden_lifetime_bug], except for hidden
+    // lifetimes in paths, which cannot be named, so we ask the user to spell them out instead.
+    let check_hidden_lifetimes = object_safe::check_hidden_lifetimes(&input.sig);
+    let native_try = native_try_bound(&output_type, &error_type);
     let to_impl_outcome: ReturnType = parse_quote! {
         -> impl ::iex::Outcome<
             Output = #output_type,
             Error = #error_type,
-        > #(+ ::iex::imp::fix_hidden_lifetime_bug::Captures<#captures>)*
+        > #native_try
+        #(+ ::iex::imp::fix_hidden_lifetime_bug::Captures<#captures>)*
     };
 
     // We used to add '#result_type: ::iex::Outcome' to the 'where' condition. This is wrong for the
@@ -290,11 +454,14 @@ fn transform_item_fn(captures: Vec<Lifetime>, input: ItemFn) -> proc_macro::Toke
     if let Err(err) = replace_try.errors.finish() {
         return err.write_errors().into();
     }
+    returns::replace_returns_in_block(&mut closure_block);
+    closure_block.stmts.insert(0, define_try_macro());
 
     let no_copy: Ident = parse_quote_spanned! { Span::mixed_site() => no_copy };
 
     let mut closure: ExprClosure = parse_quote_spanned! {
-        Span::mixed_site() => move |marker: ::iex::imp::Marker<#error_type>| {
+        Span::mixed_site() =>
+        move |marker: ::iex::imp::Marker<#body_error_type>| -> ::core::result::Result<#body_output_type, #body_error_type> {
             let #no_copy = #no_copy; // Force FnOnce inference
             #closure_block
         }
@@ -343,6 +510,7 @@ fn transform_item_fn(captures: Vec<Lifetime>, input: ItemFn) -> proc_macro::Toke
             // This span is required for dead code diagnostic
             input_span =>
             {
+                #check_hidden_lifetimes
                 #[allow(unused_imports)]
                 use ::iex::imp::_IexForward;
                 let #no_copy = ::iex::imp::NoCopy; // Force FnOnce inference
@@ -359,6 +527,18 @@ fn transform_item_fn(captures: Vec<Lifetime>, input: ItemFn) -> proc_macro::Toke
         },
     };
 
+    let doc_fn = make_doc_fn(input.attrs, input.vis, input.sig);
+
+    quote! {
+        #wrapper_fn
+        #doc_fn
+    }
+    .into()
+}
+
+fn make_doc_fn(attrs: Vec<Attribute>, vis: Visibility, sig: Signature) -> ItemFn {
+    let name = &sig.ident;
+
     let doc = format!(
         "
     <span></span>
@@ -373,16 +553,108 @@ fn transform_item_fn(captures: Vec<Lifetime>, input: ItemFn) -> proc_macro::Toke
         }}
     </style>"
     );
-    let mut doc_attrs = input.attrs;
+    let mut doc_attrs = attrs;
     doc_attrs.insert(0, parse_quote! { #[cfg(doc)] });
     doc_attrs.push(parse_quote! { #[doc = #doc] });
-    let doc_fn = ItemFn {
+    ItemFn {
         attrs: doc_attrs,
-        vis: input.vis,
-        sig: input.sig,
+        vis,
+        sig,
         block: parse_quote! {{}},
+    }
+}
+
+fn transform_async_item_fn(captures: Vec<Lifetime>, input: ItemFn) -> proc_macro::TokenStream {
+    if !captures.is_empty() {
+        return quote! {
+            compile_error!("#[iex(captures = ..)] is useless on async functions")
+        }
+        .into();
+    }
+
+    let input_span = input.span();
+
+    let result_type = match input.sig.output {
+        ReturnType::Default => parse_quote! { () },
+        ReturnType::Type(_, ref result_type) => result_type.clone(),
+    };
+    let (output_type, error_type) = result_types(&result_type);
+    // The types of the body, where `impl Trait` is not allowed
+    let body_output_type = infer_impl_trait(&output_type);
+    let body_error_type = infer_impl_trait(&error_type);
+    // async fn captures all lifetimes by itself, so neither Captures nor
+    // #[fix_hidden_lifetime_bug] is necessary here. The outcome, however, is only produced after
+    // the arguments are consumed, so it must not capture their lifetimes. The capture rules depend
+    // on the edition of the span, so this must not use the user's span, which might be edition
+    // 2024.
+    let native_try = native_try_bound(&output_type, &error_type);
+    let wrapper_sig = Signature {
+        output: parse_quote! {
+            -> impl ::iex::Outcome<Output = #output_type, Error = #error_type> #native_try
+        },
+        ..input.sig.clone()
     };
 
+    let mut future_block = input.block;
+    let mut replace_try = ReplaceTry {
+        errors: darling::Error::accumulator(),
+    };
+    replace_try.visit_block_mut(&mut future_block);
+    if let Err(err) = replace_try.errors.finish() {
+        return err.write_errors().into();
+    }
+    returns::replace_returns_in_block(&mut future_block);
+    future_block.stmts.insert(0, define_try_macro());
+
+    let mut closure: ExprClosure = parse_quote_spanned! {
+        Span::mixed_site() => move |marker: ::iex::imp::Marker<#body_error_type>| async move #future_block
+    };
+
+    let name = input.sig.ident.clone();
+
+    closure.attrs = input
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc") && !attr.path().is_ident("inline"))
+        .cloned()
+        .collect();
+    closure.attrs.insert(0, parse_quote! { #[inline(always)] });
+
+    // Doc comments must stay in the wrapper even without #[cfg(doc)] because rustc applies the
+    // missing_docs lint without cfg(doc).
+    let mut wrapper_attrs: Vec<_> = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc") || attr.path().is_ident("inline"))
+        .cloned()
+        .collect();
+    wrapper_attrs.push(parse_quote! { #[cfg(not(doc))] });
+
+    // The error is raised and caught within a single poll of IexFuture, so it never crosses an
+    // await point and can safely be stored in a thread-local even if the task migrates between
+    // threads.
+    let wrapper_fn = ItemFn {
+        attrs: wrapper_attrs,
+        vis: input.vis.clone(),
+        sig: wrapper_sig,
+        block: parse_quote_spanned! {
+            // This span is required for dead code diagnostic
+            input_span =>
+            {
+                #[allow(unused_imports)]
+                use ::iex::imp::_IexForward;
+                // We need { .. } to support the #[inline] attribute on the closure
+                let #name = { #closure };
+                ::iex::imp::IexFuture::<::core::result::Result<#body_output_type, #body_error_type>, _>::new(
+                    #name,
+                )
+                .await
+            }
+        },
+    };
+
+    let doc_fn = make_doc_fn(input.attrs, input.vis, input.sig);
+
     quote! {
         #wrapper_fn
         #doc_fn
@@ -404,11 +676,8 @@ fn transform_closure(captures: Vec<Lifetime>, input: ExprClosure) -> proc_macro:
         }
         .into();
     }
-    if let Some(asyncness) = input.asyncness {
-        return quote_spanned! {
-            asyncness.span() => compile_error!("#[iex] does not support async closures");
-        }
-        .into();
+    if input.asyncness.is_some() {
+        return transform_async_closure(input);
     }
 
     let input_span = input.span();
@@ -434,17 +703,20 @@ fn transform_closure(captures: Vec<Lifetime>, input: ExprClosure) -> proc_macro:
     if let Err(err) = replace_try.errors.finish() {
         return err.write_errors().into();
     }
+    returns::replace_returns_in_expr(&mut closure_body);
     // Workaround false positive "useless { .. } around return value" warning.
-    let closure_body = match *closure_body {
+    let mut closure_body = match *closure_body {
         Expr::Block(block) if block.attrs.is_empty() && block.label.is_none() => block.block.stmts,
         expr => vec![Stmt::Expr(expr, None)],
     };
+    closure_body.insert(0, define_try_macro());
 
     let no_copy: Ident = parse_quote_spanned! { Span::mixed_site() => no_copy };
     let closure_ident: Ident = parse_quote_spanned! { Span::mixed_site() => closure };
 
     let mut internal_closure: ExprClosure = parse_quote_spanned! {
-        Span::mixed_site() => move |marker: ::iex::imp::Marker<#error_type>| {
+        Span::mixed_site() =>
+        move |marker: ::iex::imp::Marker<#error_type>| -> ::core::result::Result<#output_type, #error_type> {
             let #no_copy = #no_copy; // Force FnOnce inference
             #(#closure_body)*
         }
@@ -491,12 +763,83 @@ fn transform_closure(captures: Vec<Lifetime>, input: ExprClosure) -> proc_macro:
     quote! { #wrapper_closure }.into()
 }
 
+fn transform_async_closure(input: ExprClosure) -> proc_macro::TokenStream {
+    if input.capture.is_none() {
+        return quote_spanned! {
+            input.asyncness.span() => compile_error!("#[iex] async closures must be `async move`");
+        }
+        .into();
+    }
+
+    let input_span = input.span();
+
+    let output_type: Type;
+    let error_type: Type;
+    match input.output {
+        ReturnType::Default => {
+            output_type = parse_quote! { _ };
+            error_type = parse_quote! { _ };
+        }
+        ReturnType::Type(_, ref result_type) => {
+            output_type = parse_quote! { <#result_type as ::iex::Outcome>::Output };
+            error_type = parse_quote! { <#result_type as ::iex::Outcome>::Error };
+        }
+    }
+
+    let mut closure_body = input.body;
+    let mut replace_try = ReplaceTry {
+        errors: darling::Error::accumulator(),
+    };
+    replace_try.visit_expr_mut(&mut closure_body);
+    if let Err(err) = replace_try.errors.finish() {
+        return err.write_errors().into();
+    }
+    returns::replace_returns_in_expr(&mut closure_body);
+
+    let try_macro = define_try_macro();
+    let mut internal_closure: ExprClosure = parse_quote_spanned! {
+        Span::mixed_site() => move |marker: ::iex::imp::Marker<#error_type>| async move {
+            #try_macro
+            #closure_body
+        }
+    };
+    internal_closure
+        .attrs
+        .insert(0, parse_quote! { #[inline(always)] });
+
+    let closure_ident: Ident = parse_quote_spanned! { Span::mixed_site() => closure };
+
+    // The closure is desugared to `move |..| async move { .. }`, so its captures are moved into
+    // the future on each call, which is why only `async move` closures are accepted.
+    let wrapper_closure = ExprClosure {
+        asyncness: None,
+        output: ReturnType::Default,
+        body: Box::new(parse_quote_spanned! {
+            // This span is required for dead code diagnostic
+            input_span =>
+            {
+                #[allow(unused_imports)]
+                use ::iex::imp::_IexForward;
+                // We need { .. } to support the #[inline] attribute on the closure
+                let #closure_ident = { #internal_closure };
+                ::iex::imp::IexFuture::<::core::result::Result<#output_type, #error_type>, _>::new(
+                    #closure_ident,
+                )
+            }
+        }),
+        ..input
+    };
+
+    quote! { #wrapper_closure }.into()
+}
+
 #[proc_macro_attribute]
 pub fn iex(
     args: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let args = match NestedMeta::parse_meta_list(args.into()) {
+    let raw_args = TokenStream::from(args);
+    let args = match NestedMeta::parse_meta_list(raw_args.clone()) {
         Ok(args) => args,
         Err(e) => return e.into_compile_error().into(),
     };
@@ -505,6 +848,17 @@ pub fn iex(
         Err(e) => return e.write_errors().into(),
     };
 
+    if args.skip {
+        return quote! {
+            compile_error!("#[iex(skip)] can only be used inside an #[iex] impl, trait or mod")
+        }
+        .into();
+    }
+
+    if let Ok(input @ (Item::Impl(_) | Item::Trait(_) | Item::Mod(_))) = parse(input.clone()) {
+        return items::transform_item(raw_args, input).into();
+    }
+
     let mut captures = Vec::new();
     for capture in args.captures {
         match parse_str::<Lifetime>(&capture) {
@@ -513,6 +867,56 @@ pub fn iex(
         }
     }
 
+    if args.object_safe && args.fn_ptr {
+        return quote! {
+            compile_error!("#[iex(object_safe)] and #[iex(fn_ptr)] cannot be used together")
+        }
+        .into();
+    }
+
+    if args.boundary {
+        if args.object_safe || args.fn_ptr {
+            return quote! {
+                compile_error!(
+                    "#[iex(boundary)] cannot be used together with #[iex(object_safe)] or #[iex(fn_ptr)]"
+                )
+            }
+            .into();
+        }
+        if !captures.is_empty() {
+            return quote! {
+                compile_error!("#[iex(captures = ..)] is useless on #[iex(boundary)] functions")
+            }
+            .into();
+        }
+        return boundary::transform_item_fn(parse_macro_input!(input as ItemFn)).into();
+    }
+
+    if args.fn_ptr {
+        if !captures.is_empty() {
+            return quote! {
+                compile_error!("#[iex(captures = ..)] is useless on #[iex(fn_ptr)] functions")
+            }
+            .into();
+        }
+        return fn_ptr::transform_item_fn(parse_macro_input!(input as ItemFn)).into();
+    }
+
+    if args.object_safe {
+        if !captures.is_empty() {
+            return quote! {
+                compile_error!("#[iex(captures = ..)] is useless on object-safe methods")
+            }
+            .into();
+        }
+        return if let Ok(input) = parse(input.clone()) {
+            object_safe::transform_item_fn(input)
+        } else {
+            object_safe::transform_trait_item_fn(parse_macro_input!(input as TraitItemFn))
+        }
+        .into();
+    }
+
     if let Ok(input) = parse(input.clone()) {
         transform_item_fn(captures, input)
     } else if let Ok(input) = parse(input.clone()) {
@@ -522,6 +926,11 @@ pub fn iex(
     }
 }
 
+#[proc_macro]
+pub fn closure(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    closure::transform_closure(parse_macro_input!(input as ExprClosure)).into()
+}
+
 #[proc_macro]
 pub fn try_block(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let mut body = parse_macro_input!(input with Block::parse_within);
@@ -535,6 +944,7 @@ pub fn try_block(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     if let Err(err) = replace_try.errors.finish() {
         return err.write_errors().into();
     }
+    body.insert(0, define_try_macro());
 
     quote_spanned! {
         Span::mixed_site() => {
@@ -555,3 +965,36 @@ pub fn try_block(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     }
     .into()
 }
+
+#[proc_macro_attribute]
+pub fn try_macro(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    if !args.is_empty() {
+        return quote! {
+            compile_error!("#[iex::try_macro] does not take arguments")
+        }
+        .into();
+    }
+
+    let mut input = parse_macro_input!(input as ItemMacro);
+    if !input.mac.path.is_ident("macro_rules") {
+        return quote_spanned! {
+            input.mac.path.span() =>
+            compile_error!("#[iex::try_macro] can only be applied to macro_rules! definitions");
+        }
+        .into();
+    }
+
+    input.mac.tokens = tokens::replace_try_in_macro_rules(input.mac.tokens, &mut try_macro_call);
+
+    // Outside #[iex] bodies, the macro uses the built-in `?`
+    quote_spanned! {
+        Span::mixed_site() =>
+        #[allow(unused_imports)]
+        use ::iex::imp::try_fallback::*;
+        #input
+    }
+    .into()
+}
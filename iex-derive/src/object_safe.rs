@@ -0,0 +1,338 @@
+use crate::{define_try_macro, make_doc_fn, make_trait_doc_fn, returns, ReplaceTry};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, parse_quote_spanned,
+    spanned::Spanned,
+    visit_mut::{self, VisitMut},
+    Attribute, Error, ExprClosure, FnArg, GenericParam, Ident, ItemFn, Lifetime, LifetimeParam,
+    ParenthesizedGenericArguments, Result, ReturnType, Signature, Stmt, TraitItemFn, Type,
+    TypeBareFn, TypeReference, Visibility,
+};
+
+// Names all elided lifetimes in argument types, so that they can be mentioned in the return type.
+// If `output` is set, elided lifetimes are replaced with it instead, just like the elision rules for
+// methods would do in the return type.
+pub(crate) struct NameElidedLifetimes {
+    pub(crate) lifetimes: Vec<Lifetime>,
+    pub(crate) output: Option<Lifetime>,
+}
+
+impl NameElidedLifetimes {
+    fn fresh(&mut self) -> Lifetime {
+        if let Some(ref lifetime) = self.output {
+            return lifetime.clone();
+        }
+        let lifetime = Lifetime::new(
+            &format!("'__iex{}", self.lifetimes.len()),
+            Span::mixed_site(),
+        );
+        self.lifetimes.push(lifetime.clone());
+        lifetime
+    }
+}
+
+impl VisitMut for NameElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, node: &mut TypeReference) {
+        if node.lifetime.is_none() {
+            node.lifetime = Some(self.fresh());
+        }
+        visit_mut::visit_type_reference_mut(self, node);
+    }
+    fn visit_lifetime_mut(&mut self, node: &mut Lifetime) {
+        if node.ident == "_" {
+            *node = self.fresh();
+        }
+    }
+    // These introduce their own elision scopes
+    fn visit_type_bare_fn_mut(&mut self, _node: &mut TypeBareFn) {}
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        _node: &mut ParenthesizedGenericArguments,
+    ) {
+    }
+}
+
+fn shim_ident(sig: &Signature) -> Ident {
+    format_ident!("__iex_object_safe_{}", sig.ident)
+}
+
+fn result_types(sig: &Signature) -> (Type, Type) {
+    let result_type: Type = match sig.output {
+        ReturnType::Default => parse_quote! { () },
+        ReturnType::Type(_, ref result_type) => (**result_type).clone(),
+    };
+    (
+        parse_quote! { <#result_type as ::iex::Outcome>::Output },
+        parse_quote! { <#result_type as ::iex::Outcome>::Error },
+    )
+}
+
+// A hidden lifetime parameter, as in `arg: Type` with `struct Type<'a>`, can't be captured by the
+// returned `impl Outcome` and can't be spelled out where argument types appear in the return type,
+// resulting in confusing errors. This statement asks for `Type<'_>` instead. Unlike an attribute on
+// the function, it does not change the lint level of the body.
+pub(crate) fn check_hidden_lifetimes(sig: &Signature) -> Stmt {
+    let arg_types = sig.inputs.iter().filter_map(|arg| match arg {
+        // `impl Trait` is not allowed in the body, and it can't hide lifetimes anyway
+        FnArg::Typed(arg) if !crate::contains_impl_trait(&arg.ty) => Some(&arg.ty),
+        _ => None,
+    });
+    parse_quote! {
+        #[deny(elided_lifetimes_in_paths)]
+        let _: ::core::marker::PhantomData<(#(#arg_types,)*)>;
+    }
+}
+
+// Checks that the method can be dispatched dynamically and returns the signature of the shim,
+// which takes an additional marker argument and returns the success value directly.
+fn shim_signature(sig: &Signature) -> Result<Signature> {
+    match sig.inputs.first() {
+        Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() => {}
+        _ => {
+            return Err(Error::new(
+                sig.ident.span(),
+                "#[iex(object_safe)] methods must take `&self` or `&mut self`",
+            ))
+        }
+    }
+    if let Some(param) = sig
+        .generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
+        return Err(Error::new(
+            param.span(),
+            "#[iex(object_safe)] methods cannot be generic over types or constants",
+        ));
+    }
+    for arg in &sig.inputs {
+        if let FnArg::Typed(arg) = arg {
+            if let Type::ImplTrait(ty) = &*arg.ty {
+                return Err(Error::new(
+                    ty.span(),
+                    "#[iex(object_safe)] methods cannot take `impl Trait` arguments",
+                ));
+            }
+        }
+    }
+    if let Some(asyncness) = sig.asyncness {
+        return Err(Error::new(
+            asyncness.span(),
+            "#[iex(object_safe)] does not support async methods",
+        ));
+    }
+
+    let (output_type, error_type) = result_types(sig);
+    let marker: Ident = parse_quote_spanned! { Span::mixed_site() => marker };
+    let mut shim_sig = sig.clone();
+    shim_sig.ident = shim_ident(sig);
+    shim_sig
+        .inputs
+        .push(parse_quote! { #marker: ::iex::imp::Marker<#error_type> });
+    shim_sig.output = parse_quote! { -> #output_type };
+    Ok(shim_sig)
+}
+
+// The public method returns a concrete type that does not mention Self, so it can be called both on
+// concrete types and on trait objects. It is provided by the trait and calls the shim lazily.
+fn public_method(attrs: &[Attribute], input_sig: &Signature, shim_name: &Ident) -> TokenStream {
+    let (mut output_type, mut error_type) = result_types(input_sig);
+    let mut sig = input_sig.clone();
+    let mut name_elided_lifetimes = NameElidedLifetimes {
+        lifetimes: Vec::new(),
+        output: None,
+    };
+    let mut receiver_lifetime = None;
+    let mut arg_names = Vec::new();
+    let mut arg_types = Vec::new();
+    for (i, arg) in sig.inputs.iter_mut().enumerate() {
+        match arg {
+            FnArg::Receiver(receiver) => {
+                let reference = receiver.reference.as_mut().unwrap();
+                let lifetime = match reference.1 {
+                    Some(ref lifetime) if lifetime.ident != "_" => lifetime.clone(),
+                    _ => name_elided_lifetimes.fresh(),
+                };
+                reference.1 = Some(lifetime.clone());
+                receiver.ty = if receiver.mutability.is_some() {
+                    parse_quote! { &#lifetime mut Self }
+                } else {
+                    parse_quote! { &#lifetime Self }
+                };
+                receiver_lifetime = Some(lifetime);
+            }
+            FnArg::Typed(arg) => {
+                name_elided_lifetimes.visit_type_mut(&mut arg.ty);
+                let arg_name = format_ident!("arg{}", i, span = Span::mixed_site());
+                arg.pat = parse_quote! { #arg_name };
+                arg_names.push(arg_name);
+                arg_types.push((*arg.ty).clone());
+            }
+        }
+    }
+    // shim_signature has checked that the receiver is present
+    let receiver_lifetime = receiver_lifetime.unwrap();
+    for lifetime in name_elided_lifetimes.lifetimes {
+        sig.generics
+            .params
+            .push(GenericParam::Lifetime(LifetimeParam::new(lifetime)));
+    }
+    let mut name_elided_lifetimes = NameElidedLifetimes {
+        lifetimes: Vec::new(),
+        output: Some(receiver_lifetime.clone()),
+    };
+    name_elided_lifetimes.visit_type_mut(&mut output_type);
+    name_elided_lifetimes.visit_type_mut(&mut error_type);
+    sig.output = parse_quote! {
+        -> ::iex::imp::IexResult<
+            #output_type,
+            #error_type,
+            ::iex::imp::DynCall<#receiver_lifetime, (#(#arg_types,)*), #output_type, #error_type>,
+        >
+    };
+
+    let mut wrapper_attrs: Vec<_> = attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("inline"))
+        .cloned()
+        .collect();
+    wrapper_attrs.insert(0, parse_quote! { #[cfg(not(doc))] });
+    wrapper_attrs.push(parse_quote! { #[inline(always)] });
+
+    let check_hidden_lifetimes = check_hidden_lifetimes(input_sig);
+
+    let this: Ident = parse_quote_spanned! { Span::mixed_site() => this };
+    let marker: Ident = parse_quote_spanned! { Span::mixed_site() => marker };
+    let call: ExprClosure = parse_quote_spanned! {
+        Span::mixed_site() => |#this, (#(#arg_names,)*), #marker| {
+            Self::#shim_name(#this, #(#arg_names,)* #marker)
+        }
+    };
+
+    quote! {
+        #(#wrapper_attrs)*
+        #sig {
+            #check_hidden_lifetimes
+            ::iex::imp::IexResult(
+                ::iex::imp::DynCall::new(self, (#(#arg_names,)*), #call),
+                ::core::marker::PhantomData,
+            )
+        }
+    }
+}
+
+pub(crate) fn transform_trait_item_fn(input: TraitItemFn) -> TokenStream {
+    // A default implementation is a default implementation of the shim
+    if let Some(block) = input.default {
+        return transform_item_fn(ItemFn {
+            attrs: input.attrs,
+            vis: Visibility::Inherited,
+            sig: input.sig,
+            block: Box::new(block),
+        });
+    }
+
+    let shim_sig = match shim_signature(&input.sig) {
+        Ok(shim_sig) => shim_sig,
+        Err(err) => return err.into_compile_error(),
+    };
+
+    let mut shim_attrs: Vec<_> = input
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc") && !attr.path().is_ident("inline"))
+        .cloned()
+        .collect();
+    shim_attrs.insert(0, parse_quote! { #[cfg(not(doc))] });
+    shim_attrs.push(parse_quote! { #[doc(hidden)] });
+
+    let public_method = public_method(&input.attrs, &input.sig, &shim_sig.ident);
+    let doc_fn = make_trait_doc_fn(input.attrs, input.sig);
+
+    quote! {
+        #(#shim_attrs)*
+        #shim_sig;
+
+        #public_method
+
+        #doc_fn
+    }
+}
+
+pub(crate) fn transform_item_fn(input: ItemFn) -> TokenStream {
+    let shim_sig = match shim_signature(&input.sig) {
+        Ok(shim_sig) => shim_sig,
+        Err(err) => return err.into_compile_error(),
+    };
+
+    let input_span = input.span();
+    let (output_type, error_type) = result_types(&input.sig);
+
+    let mut closure_block = input.block;
+    let mut replace_try = ReplaceTry {
+        errors: darling::Error::accumulator(),
+    };
+    replace_try.visit_block_mut(&mut closure_block);
+    if let Err(err) = replace_try.errors.finish() {
+        return err.write_errors();
+    }
+    returns::replace_returns_in_block(&mut closure_block);
+    closure_block.stmts.insert(0, define_try_macro());
+
+    let mut closure: ExprClosure = parse_quote_spanned! {
+        Span::mixed_site() =>
+        move |marker: ::iex::imp::Marker<#error_type>| -> ::core::result::Result<#output_type, #error_type> #closure_block
+    };
+    closure.attrs = input
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc") && !attr.path().is_ident("inline"))
+        .cloned()
+        .collect();
+    closure.attrs.insert(0, parse_quote! { #[inline(always)] });
+
+    let name = &input.sig.ident;
+    let marker: Ident = parse_quote_spanned! { Span::mixed_site() => marker };
+
+    // The shim is the function that is actually called, so #[inline] applies to it
+    let mut shim_attrs: Vec<_> = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("inline"))
+        .cloned()
+        .collect();
+    shim_attrs.insert(0, parse_quote! { #[cfg(not(doc))] });
+    shim_attrs.push(parse_quote! { #[doc(hidden)] });
+
+    // Overrides the method provided by the trait with the same code, which is necessary if the trait
+    // method itself was the default implementation processed here.
+    let public_method = public_method(&input.attrs, &input.sig, &shim_sig.ident);
+
+    let shim_fn = ItemFn {
+        attrs: shim_attrs,
+        vis: input.vis.clone(),
+        sig: shim_sig,
+        block: parse_quote_spanned! {
+            // This span is required for dead code diagnostic
+            input_span =>
+            {
+                #[allow(unused_imports)]
+                use ::iex::imp::_IexForward;
+                // We need { .. } to support the #[inline] attribute on the closure
+                #[allow(unused_mut)]
+                let mut #name = { #closure };
+                ::iex::Outcome::get_value_or_panic(#name(#marker), #marker)
+            }
+        },
+    };
+
+    let doc_fn = make_doc_fn(input.attrs, input.vis, input.sig);
+
+    quote! {
+        #shim_fn
+        #public_method
+        #doc_fn
+    }
+}
@@ -0,0 +1,140 @@
+use proc_macro2::Span;
+use syn::{
+    parse_quote_spanned,
+    visit_mut::{self, VisitMut},
+    Block, Expr, ExprAsync, ExprBreak, ExprClosure, ExprConst, ExprLoop, ExprMacro, ExprReturn,
+    ImplItemFn, Item, Stmt, TraitItemFn,
+};
+
+// Macros that never return, so their result should not be converted.
+const DIVERGING_MACROS: &[&str] = &["panic", "todo", "unimplemented", "unreachable"];
+
+// Converts the value of each `return expr` and of the tail expression to `Ok(value)`, where
+// `value` is obtained from a `Result` or an `#[iex] Result` immediately. This way, different
+// return sites can return different kinds of outcomes, and the body always evaluates to
+// `Result<T, E>`. Values of type `!`, e.g. calls to diverging functions and macros that expand to
+// `return`, are accepted too; see `_IexReturn`.
+struct ReplaceReturn;
+
+fn convert(expr: &mut Expr) {
+    let value = std::mem::replace(expr, Expr::Verbatim(Default::default()));
+    *expr = parse_quote_spanned! {
+        Span::mixed_site() =>
+        {
+            use ::iex::imp::_IexReturn;
+            // The conversion is unreachable if the value diverges
+            #[allow(unreachable_code, clippy::diverging_sub_expression)]
+            let value = (#value)._iex_return(marker);
+            ::core::result::Result::Ok(value)
+        }
+    };
+}
+
+// Whether a loop has a `break` of its own, i.e. does not necessarily diverge.
+struct HasBreak(bool);
+
+impl VisitMut for HasBreak {
+    fn visit_expr_break_mut(&mut self, node: &mut ExprBreak) {
+        self.0 = true;
+        visit_mut::visit_expr_break_mut(self, node);
+    }
+    // Breaks in nested closures and items refer to other loops; breaks in nested loops are
+    // counted conservatively
+    fn visit_expr_closure_mut(&mut self, _node: &mut ExprClosure) {}
+    fn visit_expr_async_mut(&mut self, _node: &mut ExprAsync) {}
+    fn visit_item_mut(&mut self, _node: &mut Item) {}
+}
+
+fn convert_tail_of_block(block: &mut Block) {
+    let Some(stmt) = block.stmts.last_mut() else {
+        return;
+    };
+    if let Stmt::Macro(stmt_macro) = stmt {
+        if stmt_macro.semi_token.is_none() {
+            // A macro call in the tail position is parsed as a statement
+            *stmt = Stmt::Expr(
+                Expr::Macro(ExprMacro {
+                    attrs: std::mem::take(&mut stmt_macro.attrs),
+                    mac: stmt_macro.mac.clone(),
+                }),
+                None,
+            );
+        }
+    }
+    if let Stmt::Expr(expr, None) = stmt {
+        convert_tail(expr);
+    }
+}
+
+// Whether the expression obviously never produces a value, so that its type is `!` and it must not
+// be converted.
+fn diverges(expr: &mut Expr) -> bool {
+    match expr {
+        Expr::Return(_) => true,
+        Expr::Macro(mac) => mac
+            .mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| DIVERGING_MACROS.contains(&&*segment.ident.to_string())),
+        Expr::Loop(ExprLoop { body, .. }) => {
+            let mut has_break = HasBreak(false);
+            has_break.visit_block_mut(body);
+            !has_break.0
+        }
+        _ => false,
+    }
+}
+
+fn convert_tail(expr: &mut Expr) {
+    if diverges(expr) {
+        return;
+    }
+    match expr {
+        Expr::Block(block) if block.label.is_none() => convert_tail_of_block(&mut block.block),
+        Expr::Unsafe(block) => convert_tail_of_block(&mut block.block),
+        Expr::If(expr_if) if expr_if.else_branch.is_some() => {
+            convert_tail_of_block(&mut expr_if.then_branch);
+            if let Some((_, else_branch)) = &mut expr_if.else_branch {
+                convert_tail(else_branch);
+            }
+        }
+        Expr::Match(expr_match) => {
+            for arm in &mut expr_match.arms {
+                convert_tail(&mut arm.body);
+            }
+        }
+        Expr::Paren(paren) => convert_tail(&mut paren.expr),
+        _ => convert(expr),
+    }
+}
+
+impl VisitMut for ReplaceReturn {
+    fn visit_expr_mut(&mut self, node: &mut Expr) {
+        visit_mut::visit_expr_mut(self, node);
+        if let Expr::Return(ExprReturn {
+            expr: Some(expr), ..
+        }) = node
+        {
+            convert(expr);
+        }
+    }
+    // `return` in other functions, closures and async blocks refers to them
+    fn visit_item_mut(&mut self, _node: &mut Item) {}
+    fn visit_impl_item_fn_mut(&mut self, _node: &mut ImplItemFn) {}
+    fn visit_trait_item_fn_mut(&mut self, _node: &mut TraitItemFn) {}
+    fn visit_expr_closure_mut(&mut self, _node: &mut ExprClosure) {}
+    fn visit_expr_async_mut(&mut self, _node: &mut ExprAsync) {}
+    fn visit_expr_const_mut(&mut self, _node: &mut ExprConst) {}
+}
+
+// The body must have been processed by ReplaceTry, and `marker` must be in scope.
+pub(crate) fn replace_returns_in_block(block: &mut Block) {
+    ReplaceReturn.visit_block_mut(block);
+    convert_tail_of_block(block);
+}
+
+pub(crate) fn replace_returns_in_expr(expr: &mut Expr) {
+    ReplaceReturn.visit_expr_mut(expr);
+    convert_tail(expr);
+}
@@ -0,0 +1,261 @@
+use proc_macro2::{Delimiter, Group, Spacing, TokenStream, TokenTree};
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "break", "const", "continue", "dyn", "else", "enum", "extern", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "static", "struct", "trait", "type", "unsafe", "use", "where", "while", "yield",
+];
+
+// Macros that are known to take expressions, so that `?` in their arguments can be rewritten. Other
+// macros might use `?` for something else, e.g. `quote!` or DSLs, so their arguments are left
+// alone.
+pub(crate) const EXPR_MACROS: &[&str] = &[
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "dbg",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "eprint",
+    "eprintln",
+    "format",
+    "format_args",
+    "panic",
+    "print",
+    "println",
+    "todo",
+    "unimplemented",
+    "unreachable",
+    "vec",
+    "write",
+    "writeln",
+];
+
+// Whether `tokens` end with the name and `!` of a macro call whose arguments must be left alone.
+fn is_opaque_macro_call(tokens: &[TokenTree]) -> bool {
+    match tokens {
+        [.., TokenTree::Ident(name), TokenTree::Punct(bang)] if bang.as_char() == '!' => {
+            !EXPR_MACROS.contains(&&*name.to_string())
+        }
+        _ => false,
+    }
+}
+
+fn is_punct(tree: Option<&TokenTree>, ch: char) -> bool {
+    matches!(tree, Some(TokenTree::Punct(punct)) if punct.as_char() == ch)
+}
+
+fn is_path_sep(tokens: &[TokenTree]) -> bool {
+    matches!(
+        tokens,
+        [.., TokenTree::Punct(first), TokenTree::Punct(second)]
+            if first.as_char() == ':' && first.spacing() == Spacing::Joint && second.as_char() == ':'
+    )
+}
+
+// Something that can be called or indexed, i.e. directly followed by a (..) or [..] group.
+fn is_callee(tree: Option<&TokenTree>) -> bool {
+    match tree {
+        Some(TokenTree::Ident(ident)) => !KEYWORDS.contains(&&*ident.to_string()),
+        Some(TokenTree::Group(group)) => group.delimiter() != Delimiter::Brace,
+        Some(TokenTree::Punct(punct)) => punct.as_char() == '>' || punct.as_char() == '!',
+        _ => false,
+    }
+}
+
+// Finds where the operand of a `?` at the end of `tokens` starts. `?` binds tighter than any other
+// operator, so the operand is a chain of paths, calls, indexing, field accesses and method calls.
+// Returns `tokens.len()` if there is no operand, e.g. in `?Sized` or `$(..)?`.
+fn operand_start(tokens: &[TokenTree]) -> usize {
+    let mut i = tokens.len();
+    loop {
+        match tokens[..i].last() {
+            Some(TokenTree::Group(group)) if group.delimiter() != Delimiter::Brace => {
+                if is_punct(tokens[..i - 1].last(), '$') {
+                    // Repetition in a macro_rules! matcher
+                    return tokens.len();
+                }
+                i -= 1;
+                if is_callee(tokens[..i].last()) {
+                    continue;
+                }
+                return i;
+            }
+            Some(TokenTree::Ident(ident)) => {
+                if KEYWORDS.contains(&&*ident.to_string()) && !is_punct(tokens[..i - 1].last(), '.')
+                {
+                    return i;
+                }
+                i -= 1;
+                if is_punct(tokens[..i].last(), '$') {
+                    i -= 1;
+                }
+            }
+            Some(TokenTree::Literal(_)) => i -= 1,
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '!' => {
+                // Macro call
+                if !matches!(tokens[..i - 1].last(), Some(TokenTree::Ident(_))) {
+                    return i;
+                }
+                i -= 1;
+                continue;
+            }
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '>' => {
+                // Turbofish
+                let mut depth = 0;
+                let mut j = i;
+                while j > 0 {
+                    j -= 1;
+                    if is_punct(tokens.get(j), '>') {
+                        depth += 1;
+                    } else if is_punct(tokens.get(j), '<') {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                if depth != 0 || !is_path_sep(&tokens[..j]) {
+                    return i;
+                }
+                i = j - 2;
+                continue;
+            }
+            _ => return i,
+        }
+        // The operand may continue to the left via a field access, a method call or a path
+        if is_punct(tokens[..i].last(), '.') && !is_punct(tokens[..i - 1].last(), '.') {
+            i -= 1;
+        } else if is_path_sep(&tokens[..i]) {
+            i -= 2;
+        } else {
+            return i;
+        }
+    }
+}
+
+// Whether a `|` following `tokens` starts a closure rather than being a binary operator, i.e. whether
+// it's not preceded by an operand.
+fn starts_closure(tokens: &[TokenTree]) -> bool {
+    match tokens.last() {
+        None => true,
+        Some(TokenTree::Ident(ident)) => KEYWORDS.contains(&&*ident.to_string()),
+        Some(TokenTree::Punct(punct)) => punct.as_char() != '?',
+        Some(TokenTree::Group(_) | TokenTree::Literal(_)) => false,
+    }
+}
+
+// Finds where the closure whose parameter list starts at `tokens[start]` ends. The body of a closure
+// with a return type is a block, otherwise it's assumed to extend to the next `,` or `;`.
+fn closure_end(tokens: &[TokenTree], start: usize) -> usize {
+    let body_start = if matches!(&tokens[start], TokenTree::Punct(punct) if punct.spacing() == Spacing::Joint)
+        && is_punct(tokens.get(start + 1), '|')
+    {
+        start + 2
+    } else {
+        tokens[start + 1..]
+            .iter()
+            .position(|tree| is_punct(Some(tree), '|'))
+            .map_or(tokens.len(), |i| start + 1 + i + 1)
+    };
+    let has_return_type =
+        is_punct(tokens.get(body_start), '-') && is_punct(tokens.get(body_start + 1), '>');
+    tokens[body_start..]
+        .iter()
+        .position(|tree| {
+            if has_return_type {
+                matches!(tree, TokenTree::Group(group) if group.delimiter() == Delimiter::Brace)
+            } else {
+                is_punct(Some(tree), ',') || is_punct(Some(tree), ';')
+            }
+        })
+        .map_or(tokens.len(), |i| {
+            body_start + i + usize::from(has_return_type)
+        })
+}
+
+// Finds where the `async` block starting at `tokens[start]` ends, if it is one.
+fn async_block_end(tokens: &[TokenTree], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if matches!(tokens.get(i), Some(TokenTree::Ident(ident)) if ident == "move") {
+        i += 1;
+    }
+    match tokens.get(i) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => Some(i + 1),
+        _ => None,
+    }
+}
+
+// Rewrites `operand?` into `rewrite(operand)` in a token stream that is not parseable as an
+// expression, such as macro arguments. Closures and async blocks are left alone, as `?` in them
+// doesn't propagate to the enclosing function, and so are arguments of macros not in
+// `EXPR_MACROS`.
+pub(crate) fn replace_try(
+    tokens: TokenStream,
+    rewrite: &mut impl FnMut(TokenStream) -> TokenStream,
+) -> TokenStream {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut output: Vec<TokenTree> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let tree = tokens[i].clone();
+        i += 1;
+        let skip_to = match &tree {
+            TokenTree::Punct(punct) if punct.as_char() == '|' && starts_closure(&output) => {
+                Some(closure_end(&tokens, i - 1))
+            }
+            TokenTree::Ident(ident) if ident == "async" => async_block_end(&tokens, i - 1),
+            _ => None,
+        };
+        if let Some(end) = skip_to {
+            output.extend(tokens[i - 1..end].iter().cloned());
+            i = end;
+            continue;
+        }
+        match tree {
+            TokenTree::Group(_) if is_opaque_macro_call(&output) => output.push(tree),
+            TokenTree::Group(group) => {
+                let mut new_group =
+                    Group::new(group.delimiter(), replace_try(group.stream(), rewrite));
+                new_group.set_span(group.span());
+                output.push(TokenTree::Group(new_group));
+            }
+            TokenTree::Punct(ref punct) if punct.as_char() == '?' => {
+                let start = operand_start(&output);
+                if start == output.len() {
+                    output.push(tree);
+                } else {
+                    let operand = output.drain(start..).collect();
+                    output.extend(rewrite(operand));
+                }
+            }
+            tree => output.push(tree),
+        }
+    }
+    output.into_iter().collect()
+}
+
+// Like `replace_try`, but only rewrites the transcribers of `macro_rules!` rules, as `?` means
+// something else in matchers.
+pub(crate) fn replace_try_in_macro_rules(
+    tokens: TokenStream,
+    rewrite: &mut impl FnMut(TokenStream) -> TokenStream,
+) -> TokenStream {
+    let mut output: Vec<TokenTree> = Vec::new();
+    for tree in tokens {
+        match tree {
+            TokenTree::Group(group)
+                if is_punct(output.last(), '>')
+                    && is_punct(output.len().checked_sub(2).and_then(|i| output.get(i)), '=') =>
+            {
+                let mut new_group =
+                    Group::new(group.delimiter(), replace_try(group.stream(), rewrite));
+                new_group.set_span(group.span());
+                output.push(TokenTree::Group(new_group));
+            }
+            tree => output.push(tree),
+        }
+    }
+    output.into_iter().collect()
+}
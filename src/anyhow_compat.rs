@@ -1,10 +1,9 @@
 use crate::{
     iex_result::CallWithMarker,
     imp::{IexResult, Marker},
-    Outcome,
+    BoxOutcome, IntoOutcome, NoneError, Outcome, SendBoxOutcome,
 };
 use anyhow::{Error, Result};
-use std::convert::Infallible;
 use std::fmt::Display;
 use std::marker::PhantomData;
 
@@ -85,40 +84,54 @@ impl<T, E> Context<T, E> for Result<T, E> {
     }
 }
 
-impl<T, E, Func: CallWithMarker<T, E>> Context<T, E> for IexResult<T, E, Func> {
-    type ContextOutcome<C> = IexResult<T, Error, GenericContext<Self, C>>
-    where
-        Result<(), E>: anyhow::Context<(), E>,
-        C: Display + Send + Sync + 'static;
-
-    type WithContextOutcome<C, F> = IexResult<T, Error, GenericWithContext<Self, C, F>>
-    where
-        Result<(), E>: anyhow::Context<(), E>,
-        C: Display + Send + Sync + 'static,
-        F: FnOnce() -> C;
-
-    fn context<C>(self, context: C) -> Self::ContextOutcome<C>
-    where
-        Result<(), E>: anyhow::Context<(), E>,
-        C: Display + Send + Sync + 'static,
-    {
-        IexResult(
-            GenericContext {
-                outcome: self,
-                context,
-            },
-            PhantomData,
-        )
-    }
+// All lazy outcomes wrap the error in the same way.
+macro_rules! impl_context_for_lazy_outcome {
+    ($(impl[$($generics:tt)*] for $ty:ty;)*) => {
+        $(
+            impl<$($generics)*> Context<T, E> for $ty {
+                type ContextOutcome<C> = IexResult<T, Error, GenericContext<Self, C>>
+                where
+                    Result<(), E>: anyhow::Context<(), E>,
+                    C: Display + Send + Sync + 'static;
+
+                type WithContextOutcome<C, F> = IexResult<T, Error, GenericWithContext<Self, C, F>>
+                where
+                    Result<(), E>: anyhow::Context<(), E>,
+                    C: Display + Send + Sync + 'static,
+                    F: FnOnce() -> C;
+
+                fn context<C>(self, context: C) -> Self::ContextOutcome<C>
+                where
+                    Result<(), E>: anyhow::Context<(), E>,
+                    C: Display + Send + Sync + 'static,
+                {
+                    IexResult(
+                        GenericContext {
+                            outcome: self,
+                            context,
+                        },
+                        PhantomData,
+                    )
+                }
+
+                fn with_context<C, F>(self, f: F) -> Self::WithContextOutcome<C, F>
+                where
+                    Result<(), E>: anyhow::Context<(), E>,
+                    C: Display + Send + Sync + 'static,
+                    F: FnOnce() -> C,
+                {
+                    IexResult(GenericWithContext { outcome: self, f }, PhantomData)
+                }
+            }
+        )*
+    };
+}
 
-    fn with_context<C, F>(self, f: F) -> Self::WithContextOutcome<C, F>
-    where
-        Result<(), E>: anyhow::Context<(), E>,
-        C: Display + Send + Sync + 'static,
-        F: FnOnce() -> C,
-    {
-        IexResult(GenericWithContext { outcome: self, f }, PhantomData)
-    }
+impl_context_for_lazy_outcome! {
+    impl[T, E, Func: CallWithMarker<T, E>] for IexResult<T, E, Func>;
+    impl['a, T, E] for BoxOutcome<'a, T, E>;
+    impl['a, T, E] for SendBoxOutcome<'a, T, E>;
+    impl[T, E, R: IntoOutcome<IntoOutcome: Outcome<Output = T, Error = E>>] for R;
 }
 
 pub struct GenericContext<R, C> {
@@ -156,7 +169,7 @@ where
     }
 }
 
-impl<T> Context<T, Infallible> for Option<T> {
+impl<T> Context<T, NoneError> for Option<T> {
     type ContextOutcome<C> = Result<T>
     where
         C: Display + Send + Sync + 'static;
@@ -0,0 +1,20 @@
+use crate::NoneError;
+
+// Converts the result of an #[iex(boundary)] body to the declared return type.
+pub trait FromResult<T, E> {
+    fn from_result(result: Result<T, E>) -> Self;
+}
+
+impl<T, E> FromResult<T, E> for Result<T, E> {
+    #[inline(always)]
+    fn from_result(result: Result<T, E>) -> Self {
+        result
+    }
+}
+
+impl<T> FromResult<T, NoneError> for Option<T> {
+    #[inline(always)]
+    fn from_result(result: Result<T, NoneError>) -> Self {
+        result.ok()
+    }
+}
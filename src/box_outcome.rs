@@ -0,0 +1,269 @@
+use crate::{
+    imp::{IexResult, Marker},
+    native_try::impl_outcome,
+    outcome::Sealed,
+    Outcome,
+};
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of, MaybeUninit};
+
+// Outcomes that fit in here are stored inline, larger ones are boxed. Three words is enough for most
+// #[iex] functions taking a receiver and a couple of arguments.
+type Storage = MaybeUninit<[usize; 3]>;
+
+type Invariant<T> = PhantomData<fn(T) -> T>;
+
+/// A type-erased `#[iex] Result`.
+///
+/// [`#[iex]`](macro@crate::iex) functions return opaque types, which cannot be named in struct
+/// fields, collections, trait associated types, or function pointer types. `BoxOutcome` erases the
+/// type of an [`Outcome`], while still propagating errors by unwinding, so `?`,
+/// [`map_err`](Outcome::map_err) and [`into_result`](Outcome::into_result) work just like with
+/// `#[iex] Result`.
+///
+/// Small outcomes, which includes most `#[iex]` calls, are stored inline without allocation.
+///
+/// This type is not [`Send`]. Use [`SendBoxOutcome`] if you need to send the outcome to another
+/// thread.
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, BoxOutcome, Outcome};
+///
+/// struct Vm {
+///     stack: Vec<i32>,
+/// }
+///
+/// #[iex]
+/// fn push(vm: &mut Vm) -> Result<(), &'static str> {
+///     vm.stack.push(1);
+///     Ok(())
+/// }
+///
+/// #[iex]
+/// fn add(vm: &mut Vm) -> Result<(), &'static str> {
+///     let a = vm.stack.pop().ok_or("Stack underflow")?;
+///     let b = vm.stack.pop().ok_or("Stack underflow")?;
+///     vm.stack.push(a + b);
+///     Ok(())
+/// }
+///
+/// type Handler = fn(&mut Vm) -> BoxOutcome<'_, (), &'static str>;
+///
+/// const HANDLERS: [Handler; 2] = [
+///     |vm| BoxOutcome::new(push(vm)),
+///     |vm| BoxOutcome::new(add(vm)),
+/// ];
+///
+/// #[iex]
+/// fn run(vm: &mut Vm, code: &[u8]) -> Result<(), &'static str> {
+///     for &opcode in code {
+///         HANDLERS[opcode as usize](vm)?;
+///     }
+///     Ok(())
+/// }
+///
+/// let mut vm = Vm { stack: Vec::new() };
+/// assert_eq!(run(&mut vm, &[0, 0, 1]).into_result(), Ok(()));
+/// assert_eq!(vm.stack, [2]);
+/// assert_eq!(run(&mut vm, &[1]).into_result(), Err("Stack underflow"));
+/// ```
+///
+/// `BoxOutcome` is invariant in `T` and `E`, so the lifetimes of the error cannot be extended:
+///
+/// ```compile_fail
+/// use iex::BoxOutcome;
+///
+/// fn extend<'a>(outcome: BoxOutcome<'a, (), &'a str>) -> BoxOutcome<'a, (), &'static str> {
+///     outcome
+/// }
+/// ```
+pub struct BoxOutcome<'a, T, E> {
+    data: Storage,
+    call: unsafe fn(*mut Storage, Marker<E>) -> T,
+    drop: unsafe fn(*mut Storage),
+    // Not Send, not Sync, and borrows for 'a
+    phantom: PhantomData<Box<dyn FnOnce() + 'a>>,
+    // Invariant in T and E, as `call` would otherwise make it contravariant in E
+    invariant: Invariant<(T, E)>,
+}
+
+const fn is_inline<R>() -> bool {
+    size_of::<R>() <= size_of::<Storage>() && align_of::<R>() <= align_of::<Storage>()
+}
+
+unsafe fn call_inline<R: Outcome>(data: *mut Storage, marker: Marker<R::Error>) -> R::Output {
+    data.cast::<R>().read().get_value_or_panic(marker)
+}
+
+unsafe fn call_boxed<R: Outcome>(data: *mut Storage, marker: Marker<R::Error>) -> R::Output {
+    data.cast::<Box<R>>().read().get_value_or_panic(marker)
+}
+
+unsafe fn drop_erased<S>(data: *mut Storage) {
+    data.cast::<S>().drop_in_place();
+}
+
+impl<'a, T, E> BoxOutcome<'a, T, E> {
+    /// Erase the type of an outcome.
+    #[inline]
+    pub fn new<R: Outcome<Output = T, Error = E> + 'a>(outcome: R) -> Self {
+        let mut data = Storage::uninit();
+        unsafe {
+            if is_inline::<R>() {
+                data.as_mut_ptr().cast::<R>().write(outcome);
+                Self::from_raw(data, call_inline::<R>, drop_erased::<R>)
+            } else {
+                data.as_mut_ptr().cast::<Box<R>>().write(Box::new(outcome));
+                Self::from_raw(data, call_boxed::<R>, drop_erased::<Box<R>>)
+            }
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn from_raw(
+        data: Storage,
+        call: unsafe fn(*mut Storage, Marker<E>) -> T,
+        drop: unsafe fn(*mut Storage),
+    ) -> Self {
+        Self {
+            data,
+            call,
+            drop,
+            phantom: PhantomData,
+            invariant: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    fn into_iex_result(self) -> IexResult<T, E, impl FnOnce(Marker<E>) -> T + use<'a, T, E>> {
+        IexResult(move |marker| self.get_value_or_panic(marker), PhantomData)
+    }
+}
+
+impl<T, E> Drop for BoxOutcome<'_, T, E> {
+    fn drop(&mut self) {
+        unsafe { (self.drop)(&mut self.data) }
+    }
+}
+
+impl<T, E> Sealed for BoxOutcome<'_, T, E> {}
+
+impl<'a, T, E> Outcome for BoxOutcome<'a, T, E> {
+    type Output = T;
+    type Error = E;
+
+    #[inline(always)]
+    fn get_value_or_panic(self, marker: Marker<E>) -> T {
+        let mut this = std::mem::ManuallyDrop::new(self);
+        unsafe { (this.call)(&mut this.data, marker) }
+    }
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn inspect_err<F>(self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Self::Error),
+    {
+    }
+
+    #[cfg(not(doc))]
+    fn inspect_err<F>(self, f: F) -> impl_outcome!(T, E)
+    where
+        F: FnOnce(&Self::Error),
+    {
+        self.into_iex_result().inspect_err(f)
+    }
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn map_err<F, O>(self, op: O) -> Result<T, F>
+    where
+        O: FnOnce(E) -> F,
+    {
+    }
+
+    #[cfg(not(doc))]
+    fn map_err<F, O>(self, op: O) -> impl_outcome!(Self::Output, F)
+    where
+        O: FnOnce(E) -> F,
+    {
+        self.into_iex_result().map_err(op)
+    }
+
+    fn into_result(self) -> Result<T, E> {
+        self.into_iex_result().into_result()
+    }
+}
+
+/// A type-erased `#[iex] Result` that can be sent to another thread.
+///
+/// This is the [`Send`] version of [`BoxOutcome`]. It can be converted to a [`BoxOutcome`] with
+/// [`From`].
+pub struct SendBoxOutcome<'a, T, E>(BoxOutcome<'a, T, E>);
+
+// SAFETY: SendBoxOutcome can only be constructed from a Send outcome.
+unsafe impl<T, E> Send for SendBoxOutcome<'_, T, E> {}
+
+impl<'a, T, E> SendBoxOutcome<'a, T, E> {
+    /// Erase the type of an outcome.
+    #[inline]
+    pub fn new<R: Outcome<Output = T, Error = E> + Send + 'a>(outcome: R) -> Self {
+        Self(BoxOutcome::new(outcome))
+    }
+}
+
+impl<'a, T, E> From<SendBoxOutcome<'a, T, E>> for BoxOutcome<'a, T, E> {
+    fn from(outcome: SendBoxOutcome<'a, T, E>) -> Self {
+        outcome.0
+    }
+}
+
+impl<T, E> Sealed for SendBoxOutcome<'_, T, E> {}
+
+impl<'a, T, E> Outcome for SendBoxOutcome<'a, T, E> {
+    type Output = T;
+    type Error = E;
+
+    #[inline(always)]
+    fn get_value_or_panic(self, marker: Marker<E>) -> T {
+        self.0.get_value_or_panic(marker)
+    }
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn inspect_err<F>(self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Self::Error),
+    {
+    }
+
+    #[cfg(not(doc))]
+    fn inspect_err<F>(self, f: F) -> impl_outcome!(T, E)
+    where
+        F: FnOnce(&Self::Error),
+    {
+        self.0.inspect_err(f)
+    }
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn map_err<F, O>(self, op: O) -> Result<T, F>
+    where
+        O: FnOnce(E) -> F,
+    {
+    }
+
+    #[cfg(not(doc))]
+    fn map_err<F, O>(self, op: O) -> impl_outcome!(Self::Output, F)
+    where
+        O: FnOnce(E) -> F,
+    {
+        self.0.map_err(op)
+    }
+
+    fn into_result(self) -> Result<T, E> {
+        self.0.into_result()
+    }
+}
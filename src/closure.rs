@@ -0,0 +1,128 @@
+use crate::{
+    imp::{IexResult, Marker},
+    native_try::impl_outcome,
+};
+use std::marker::PhantomData;
+
+/// An `#[iex]` closure created by [`closure!`](crate::closure).
+///
+/// Unlike regular closures, `IexClosure` is called via methods. The arguments are passed as a
+/// tuple, and the result is an `#[iex] Result`:
+///
+/// - [`call`](Self::call) borrows the closure immutably, like [`Fn`],
+/// - [`call_mut`](Self::call_mut) borrows the closure mutably, like [`FnMut`],
+/// - [`call_once`](Self::call_once) consumes the closure, like [`FnOnce`].
+///
+/// Which of these methods are available depends on how the closure uses its captures, just like
+/// with regular closures.
+///
+/// `IexClosure` also implements [`IexFnOnce`](crate::IexFnOnce), [`IexFnMut`](crate::IexFnMut)
+/// and [`IexFn`](crate::IexFn) accordingly, so it can be passed to functions accepting `#[iex]`
+/// callbacks.
+pub struct IexClosure<F>(F);
+
+impl<F> IexClosure<F> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn new(func: F) -> Self {
+        Self(func)
+    }
+
+    /// Call the closure by reference.
+    #[inline(always)]
+    pub fn call<A, E>(
+        &self,
+        args: A,
+    ) -> impl_outcome!(<F as RawFnOnce<A, E>>::Output, E; + use<'_, F, A, E>)
+    where
+        F: RawFn<A, E>,
+    {
+        IexResult(move |marker| self.0.call_raw(args, marker), PhantomData)
+    }
+
+    /// Call the closure by mutable reference.
+    #[inline(always)]
+    pub fn call_mut<A, E>(
+        &mut self,
+        args: A,
+    ) -> impl_outcome!(<F as RawFnOnce<A, E>>::Output, E; + use<'_, F, A, E>)
+    where
+        F: RawFnMut<A, E>,
+    {
+        IexResult(move |marker| self.0.call_mut_raw(args, marker), PhantomData)
+    }
+
+    /// Call the closure by value.
+    #[inline(always)]
+    pub fn call_once<A, E>(self, args: A) -> impl_outcome!(<F as RawFnOnce<A, E>>::Output, E)
+    where
+        F: RawFnOnce<A, E>,
+    {
+        IexResult(
+            move |marker| self.0.call_once_raw(args, marker),
+            PhantomData,
+        )
+    }
+}
+
+// Closures that take the marker as the last argument, with the other arguments passed as a tuple.
+pub trait RawFnOnce<A, E> {
+    type Output;
+    fn call_once_raw(self, args: A, marker: Marker<E>) -> Self::Output;
+}
+
+pub trait RawFnMut<A, E>: RawFnOnce<A, E> {
+    fn call_mut_raw(&mut self, args: A, marker: Marker<E>) -> Self::Output;
+}
+
+pub trait RawFn<A, E>: RawFnMut<A, E> {
+    fn call_raw(&self, args: A, marker: Marker<E>) -> Self::Output;
+}
+
+macro_rules! impl_raw_fn {
+    ($($arg:ident)*) => {
+        #[allow(non_snake_case)]
+        impl<Func, T, E, $($arg,)*> RawFnOnce<($($arg,)*), E> for Func
+        where
+            Func: FnOnce($($arg,)* Marker<E>) -> T,
+        {
+            type Output = T;
+            #[inline(always)]
+            fn call_once_raw(self, ($($arg,)*): ($($arg,)*), marker: Marker<E>) -> T {
+                self($($arg,)* marker)
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<Func, T, E, $($arg,)*> RawFnMut<($($arg,)*), E> for Func
+        where
+            Func: FnMut($($arg,)* Marker<E>) -> T,
+        {
+            #[inline(always)]
+            fn call_mut_raw(&mut self, ($($arg,)*): ($($arg,)*), marker: Marker<E>) -> T {
+                self($($arg,)* marker)
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<Func, T, E, $($arg,)*> RawFn<($($arg,)*), E> for Func
+        where
+            Func: Fn($($arg,)* Marker<E>) -> T,
+        {
+            #[inline(always)]
+            fn call_raw(&self, ($($arg,)*): ($($arg,)*), marker: Marker<E>) -> T {
+                self($($arg,)* marker)
+            }
+        }
+    };
+}
+
+impl_raw_fn!();
+impl_raw_fn!(A1);
+impl_raw_fn!(A1 A2);
+impl_raw_fn!(A1 A2 A3);
+impl_raw_fn!(A1 A2 A3 A4);
+impl_raw_fn!(A1 A2 A3 A4 A5);
+impl_raw_fn!(A1 A2 A3 A4 A5 A6);
+impl_raw_fn!(A1 A2 A3 A4 A5 A6 A7);
+impl_raw_fn!(A1 A2 A3 A4 A5 A6 A7 A8);
@@ -0,0 +1,58 @@
+use crate::{iex_result::CallWithMarker, imp::Marker};
+use std::marker::PhantomData;
+use std::mem::{size_of, MaybeUninit};
+
+// Large enough to store a (possibly fat) reference.
+type ErasedRef = MaybeUninit<[usize; 2]>;
+
+/// A lazy call to an object-safe shim of an `#[iex(object_safe)]` trait method.
+///
+/// This type does not mention the type of the receiver, so that it can be named in the return type
+/// of an object-safe method.
+pub struct DynCall<'a, A, T, E> {
+    this: ErasedRef,
+    call: *const (),
+    trampoline: unsafe fn(ErasedRef, *const (), A, Marker<E>) -> T,
+    args: A,
+    phantom: PhantomData<&'a ()>,
+}
+
+unsafe fn trampoline<R, A, T, E>(
+    this: ErasedRef,
+    call: *const (),
+    args: A,
+    marker: Marker<E>,
+) -> T {
+    let this = this.as_ptr().cast::<R>().read_unaligned();
+    let call = std::mem::transmute::<*const (), fn(R, A, Marker<E>) -> T>(call);
+    call(this, args, marker)
+}
+
+impl<'a, A, T, E> DynCall<'a, A, T, E> {
+    // `R` is `&'a S` or `&'a mut S` for some `S: ?Sized`. References don't need to be dropped, so
+    // `DynCall` doesn't need to track whether the call has happened.
+    #[inline(always)]
+    pub fn new<R: 'a>(this: R, args: A, call: fn(R, A, Marker<E>) -> T) -> Self {
+        const { assert!(size_of::<R>() <= size_of::<ErasedRef>()) };
+        let mut erased = ErasedRef::uninit();
+        unsafe {
+            erased.as_mut_ptr().cast::<R>().write_unaligned(this);
+        }
+        Self {
+            this: erased,
+            call: call as *const (),
+            trampoline: trampoline::<R, A, T, E>,
+            args,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<A, T, E> CallWithMarker<T, E> for DynCall<'_, A, T, E> {
+    #[inline(always)]
+    fn call_with_marker(self, marker: Marker<E>) -> T {
+        // SAFETY: `trampoline` was instantiated with the same `R` that `this` and `call` were
+        // erased from.
+        unsafe { (self.trampoline)(self.this, self.call, self.args, marker) }
+    }
+}
@@ -1,81 +1,339 @@
-use std::mem::{align_of, size_of, MaybeUninit};
+use crate::{unwind, ErrorInfo, IexPanic, EXCEPTION};
+use std::mem::{align_of, size_of, ManuallyDrop, MaybeUninit};
+use std::panic::AssertUnwindSafe;
 
-pub(crate) struct Exception {
-    data: MaybeUninit<[usize; 8]>,
+// Errors that fit in here are stored inline, larger ones are boxed.
+type Storage = MaybeUninit<[usize; 7]>;
+
+// A type-erased error.
+struct Entry {
+    data: Storage,
+    drop: unsafe fn(*mut Storage),
+    tag: Tag,
+    // The number of errors pushed on this thread before this one, which tells regions whether the
+    // error was thrown after they started.
+    id: u64,
+    // Whether the error is still being propagated. Cleared when its panic is swallowed.
+    in_flight: bool,
 }
 
-#[repr(C)]
-struct Just<T> {
-    discriminant: usize,
-    value: MaybeUninit<T>,
+// Describes the error. The type is always checked when the error is read, because errors that were
+// never caught may be left on the stack, and a region may mistake them for its own. In checked
+// builds, also remembers where the error was thrown.
+#[derive(Clone, Copy)]
+struct Tag {
+    type_name: &'static str,
+    type_id: std::any::TypeId,
+    #[cfg(any(debug_assertions, feature = "checked"))]
+    location: &'static std::panic::Location<'static>,
 }
 
-impl Exception {
-    pub(crate) const fn new() -> Self {
+impl Tag {
+    #[cfg_attr(any(debug_assertions, feature = "checked"), track_caller)]
+    fn new<T>() -> Self {
         Self {
-            data: MaybeUninit::zeroed(),
+            type_name: std::any::type_name::<T>(),
+            type_id: typeid::of::<T>(),
+            #[cfg(any(debug_assertions, feature = "checked"))]
+            location: std::panic::Location::caller(),
         }
     }
 
-    const fn is_small<T>() -> bool {
-        size_of::<Just<T>>() <= size_of::<Exception>()
+    fn to_info(self) -> ErrorInfo {
+        #[cfg(any(debug_assertions, feature = "checked"))]
+        let location = Some(self.location);
+        #[cfg(not(any(debug_assertions, feature = "checked")))]
+        let location = None;
+        ErrorInfo::new(self.type_name, location)
     }
 
-    unsafe fn write_raw<T>(&mut self, value: T) {
-        let ptr = self.data.as_mut_ptr().cast::<T>();
-        if align_of::<T>() <= align_of::<usize>() {
-            ptr.write(value);
-        } else {
-            ptr.write_unaligned(value);
-        }
+    fn check<T>(&self) {
+        assert!(
+            self.type_id == typeid::of::<T>(),
+            "{} was read as `{}`",
+            self.to_info(),
+            std::any::type_name::<T>(),
+        );
     }
 
-    pub(crate) fn write<T>(&mut self, value: T) {
+    #[cfg(any(debug_assertions, feature = "checked"))]
+    fn report_unconsumed(self) -> ! {
+        panic!(
+            "{} was never caught; was its panic swallowed by `catch_unwind`?",
+            self.to_info(),
+        );
+    }
+}
+
+const fn is_small<T>() -> bool {
+    size_of::<T>() <= size_of::<Storage>()
+}
+
+unsafe fn write<T>(data: *mut Storage, value: T) {
+    let ptr = data.cast::<T>();
+    if align_of::<T>() <= align_of::<Storage>() {
+        ptr.write(value);
+    } else {
+        ptr.write_unaligned(value);
+    }
+}
+
+unsafe fn read<T>(data: *mut Storage) -> T {
+    let ptr = data.cast::<T>();
+    if align_of::<T>() <= align_of::<Storage>() {
+        ptr.read()
+    } else {
+        ptr.read_unaligned()
+    }
+}
+
+unsafe fn drop_erased<S>(data: *mut Storage) {
+    drop(read::<S>(data));
+}
+
+impl Entry {
+    #[cfg_attr(any(debug_assertions, feature = "checked"), track_caller)]
+    fn new<T>(value: T, id: u64) -> Self {
+        let mut data = Storage::uninit();
+        let tag = Tag::new::<T>();
         unsafe {
-            if Self::is_small::<T>() {
-                self.write_raw(Just {
-                    discriminant: 1,
-                    value: MaybeUninit::new(value),
-                });
+            if is_small::<T>() {
+                write(&mut data, value);
+                Self {
+                    data,
+                    drop: drop_erased::<T>,
+                    tag,
+                    id,
+                    in_flight: true,
+                }
             } else {
-                self.write_raw(Some(Box::new(value)));
+                write(&mut data, Box::new(value));
+                Self {
+                    data,
+                    drop: drop_erased::<Box<T>>,
+                    tag,
+                    id,
+                    in_flight: true,
+                }
             }
         }
     }
 
-    pub(crate) fn clear(&mut self) {
-        unsafe { self.write_raw(0usize) }
+    // The entry must have been created from a value of type `T`.
+    unsafe fn into_inner<T>(self) -> T {
+        self.tag.check::<T>();
+        let mut this = ManuallyDrop::new(self);
+        if is_small::<T>() {
+            read(&mut this.data)
+        } else {
+            *read::<Box<T>>(&mut this.data)
+        }
     }
+}
 
-    unsafe fn read_raw<T>(&self) -> T {
-        let ptr = self.data.as_ptr().cast::<T>();
-        if align_of::<T>() <= align_of::<usize>() {
-            ptr.read()
-        } else {
-            ptr.read_unaligned()
+impl Drop for Entry {
+    fn drop(&mut self) {
+        unsafe { (self.drop)(&mut self.data) }
+    }
+}
+
+// Errors that are being propagated on this thread. Each thrown error is pushed on top, and each
+// region that maps errors remembers how many errors were pushed before its start, so that code
+// running during propagation, e.g. in destructors or error mappers, can throw and catch errors of
+// its own without clobbering the errors of outer regions.
+//
+// Errors whose panic was swallowed by a foreign `catch_unwind` stay on the stack until the next
+// throw, but are no longer in flight, so that regions don't mistake them for their own.
+pub(crate) struct ExceptionStack {
+    entries: Vec<Entry>,
+    pushed: u64,
+}
+
+impl ExceptionStack {
+    pub(crate) const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            pushed: 0,
         }
     }
 
-    pub(crate) unsafe fn read<T>(&self) -> Option<T> {
-        if Self::is_small::<T>() {
-            let just = self.read_raw::<Just<T>>();
-            if just.discriminant == 0 {
-                None
-            } else {
-                Some(just.value.assume_init())
-            }
-        } else {
-            self.read_raw::<Option<Box<T>>>().map(|b| *b)
+    #[cfg_attr(any(debug_assertions, feature = "checked"), track_caller)]
+    pub(crate) fn push<T>(&mut self, value: T) {
+        self.entries.push(Entry::new(value, self.pushed));
+        self.pushed += 1;
+    }
+
+    // The index of the most recently thrown error that is still in flight.
+    fn in_flight(&self) -> Option<usize> {
+        self.entries.iter().rposition(|entry| entry.in_flight)
+    }
+
+    // Takes the error in flight, which is the one being caught.
+    //
+    // The error must be of type `T`.
+    pub(crate) unsafe fn take<T>(&mut self) -> T {
+        let index = self.in_flight().expect(
+            "iex error was propagated, but is missing from the exception stack of this thread; \
+             did its unwinding cross a thread boundary?",
+        );
+        self.entries.remove(index).into_inner()
+    }
+
+    // Takes the error in flight if it was thrown within the region that started at `start`, which
+    // is then the one propagated out of the region. Returns `None` if nothing was thrown in the
+    // region, i.e. the region is unwound by a foreign panic.
+    //
+    // This is called during unwinding, where panicking aborts the process, so an error of a wrong
+    // type is reported as lost instead of failing the type check. This happens if the payload of an
+    // earlier error was leaked instead of dropped, and a foreign panic unwinds the region.
+    pub(crate) fn take_since<T>(&mut self, start: u64) -> Option<T> {
+        let index = self.in_flight()?;
+        if self.entries[index].id < start {
+            return None;
+        }
+        let entry = self.entries.remove(index);
+        if entry.tag.type_id != typeid::of::<T>() {
+            unwind::report_uncaught(&entry.tag.to_info());
+            return None;
         }
+        // SAFETY: The type was checked above.
+        Some(unsafe { entry.into_inner() })
     }
 
-    pub(crate) unsafe fn read_unchecked<T>(&self) -> T {
-        if Self::is_small::<T>() {
-            self.read_raw::<Just<T>>().value.assume_init()
-        } else {
-            *self.read_raw::<Box<T>>()
+    // The error in flight was swallowed, so it must not be caught by anyone.
+    fn swallow(&mut self) {
+        if let Some(index) = self.in_flight() {
+            self.entries[index].in_flight = false;
         }
     }
+
+    // Errors are only stored while they are propagated, so outside of unwinding, anything left on
+    // the stack was thrown, but never caught, e.g. because a foreign `catch_unwind` swallowed the
+    // panic. Such errors are reported to the uncaught error handler and dropped, or cause a panic in
+    // checked builds.
+    fn discard_stale(&mut self) {
+        if self.entries.is_empty() || std::thread::panicking() {
+            return;
+        }
+        // Move the errors out before dropping them, as their destructors may use the stack.
+        let stale = std::mem::take(&mut self.entries);
+        #[cfg(any(debug_assertions, feature = "checked"))]
+        {
+            let tag = stale[0].tag;
+            drop(stale);
+            tag.report_unconsumed();
+        }
+        #[cfg(not(any(debug_assertions, feature = "checked")))]
+        for entry in stale {
+            unwind::report_uncaught(&entry.tag.to_info());
+        }
+    }
+}
+
+// Runs at thread exit. Errors still on the stack at this point have been lost, most likely because
+// their unwinding left the thread.
+impl Drop for ExceptionStack {
+    fn drop(&mut self) {
+        for entry in &self.entries {
+            unwind::report_uncaught(&entry.tag.to_info());
+        }
+    }
+}
+
+// Describes the error in flight on this thread.
+pub(crate) fn in_flight_info() -> Option<ErrorInfo> {
+    EXCEPTION
+        .try_with(|exception| {
+            let stack = unsafe { &*exception.get() };
+            Some(stack.entries[stack.in_flight()?].tag.to_info())
+        })
+        .ok()
+        .flatten()
+}
+
+// Called when the panic of the error in flight is swallowed, i.e. its payload is dropped.
+pub(crate) fn swallow() {
+    // The payload may be dropped during thread exit, when the stack is already gone
+    let _ = EXCEPTION.try_with(|exception| unsafe { &mut *exception.get() }.swallow());
+}
+
+// Identifies the errors thrown after the start of a region, for `take_since`. Outside of unwinding,
+// no error is in flight, as swallowed errors are marked as such, so the region can be assumed to
+// start at the very beginning. This avoids accessing the thread-local on the success path.
+#[inline(always)]
+pub(crate) fn region_start() -> u64 {
+    if std::thread::panicking() {
+        EXCEPTION.with(|exception| unsafe { &*exception.get() }.pushed)
+    } else {
+        0
+    }
+}
+
+// Whether an error thrown after the start of the region is being propagated. Errors whose panic
+// was swallowed don't count, even if a foreign panic is unwinding the region.
+pub(crate) fn is_propagating_since(start: u64) -> bool {
+    std::thread::panicking()
+        && EXCEPTION.with(|exception| {
+            let stack = unsafe { &*exception.get() };
+            stack
+                .in_flight()
+                .is_some_and(|index| stack.entries[index].id >= start)
+        })
+}
+
+// The state of the thread at the start of a scope, used to find out how the scope is left.
+pub(crate) struct Scope {
+    panicking: bool,
+    start: u64,
+}
+
+impl Scope {
+    pub(crate) fn enter() -> Self {
+        Self {
+            panicking: std::thread::panicking(),
+            start: region_start(),
+        }
+    }
+
+    // Whether an error thrown within the scope is being propagated out of it.
+    pub(crate) fn is_propagating(&self) -> bool {
+        is_propagating_since(self.start)
+    }
+
+    // Whether a foreign panic started within the scope is unwinding out of it.
+    pub(crate) fn is_panicking(&self) -> bool {
+        !self.panicking && std::thread::panicking() && !self.is_propagating()
+    }
+}
+
+// Throws `error` as an exception.
+#[cold]
+#[cfg_attr(any(debug_assertions, feature = "checked"), track_caller)]
+pub(crate) fn throw<E>(error: E) -> ! {
+    // Not pushing from within the closure, so that #[track_caller] sees the caller of throw.
+    let exception = EXCEPTION.with(|exception| exception.get());
+    unsafe { &mut *exception }.discard_stale();
+    unsafe { &mut *exception }.push(error);
+    std::panic::resume_unwind(Box::new(IexPanic::new()))
+}
+
+// Calls `f`, catching the error of type `E` it throws, if any. Other panics are propagated.
+//
+// `f` must only throw errors of type `E`.
+#[inline(always)]
+pub(crate) unsafe fn catch<T, E>(f: impl FnOnce() -> T) -> Result<T, E> {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).map_err(
+        #[cold]
+        |payload| {
+            if !payload.is::<IexPanic>() {
+                std::panic::resume_unwind(payload);
+            }
+            // Dropping the payload would mark the error as swallowed. The payload is zero-sized, so
+            // forgetting it does not leak memory.
+            std::mem::forget(payload);
+            EXCEPTION.with(|exception| unsafe { (*exception.get()).take() })
+        },
+    )
 }
 
 #[cfg(test)]
@@ -84,8 +342,27 @@ mod test {
 
     #[test]
     fn overaligned() {
-        let mut exc = Exception::new();
-        exc.write(123u128);
-        assert_eq!(unsafe { exc.read_unchecked::<u128>() }, 123);
+        let mut stack = ExceptionStack::new();
+        stack.push(123u128);
+        assert_eq!(unsafe { stack.take::<u128>() }, 123);
+    }
+
+    #[test]
+    fn nested() {
+        let mut stack = ExceptionStack::new();
+        stack.push(String::from("outer"));
+        stack.push([1u64; 16]);
+        assert_eq!(unsafe { stack.take::<[u64; 16]>() }, [1; 16]);
+        assert_eq!(unsafe { stack.take::<String>() }, "outer");
+    }
+
+    #[test]
+    #[should_panic(expected = "iex error of type `u8`")]
+    fn wrong_type() {
+        let mut stack = ExceptionStack::new();
+        stack.push(1u8);
+        unsafe {
+            stack.take::<i8>();
+        }
     }
 }
@@ -1,10 +1,12 @@
-use crate::{imp::Marker, EXCEPTION};
+use crate::{exception, imp::Marker, EXCEPTION};
 use std::marker::PhantomData;
 use std::mem::ManuallyDrop;
 
 pub struct ExceptionMapper<S, T, U, F: FnOnce(S, T) -> U> {
     state: ManuallyDrop<S>,
     f: ManuallyDrop<F>,
+    // Identifies the errors thrown within the mapped region
+    start: u64,
     phantom: PhantomData<fn(S, T) -> U>,
 }
 
@@ -13,6 +15,7 @@ impl<S, T, U, F: FnOnce(S, T) -> U> ExceptionMapper<S, T, U, F> {
         Self {
             state: ManuallyDrop::new(state),
             f: ManuallyDrop::new(f),
+            start: exception::region_start(),
             phantom: PhantomData,
         }
     }
@@ -38,12 +41,17 @@ impl<S, T, U, F: FnOnce(S, T) -> U> Drop for ExceptionMapper<S, T, U, F> {
         // Resolve TLS just once
         EXCEPTION.with(|exception| unsafe {
             let exception = exception.get();
-            // Dereference twice instead of keeping a &mut around, because self.0() may call a
-            // function that uses 'exception'.
-            if let Some(error) = (*exception).read::<T>() {
+            // If nothing was thrown in the region, this is a foreign panic. Dereference twice
+            // instead of keeping a &mut around, because f() may call a function that uses
+            // 'exception'.
+            if let Some(error) = (*exception).take_since::<T>(self.start) {
                 let state = ManuallyDrop::take(&mut self.state);
                 let f = ManuallyDrop::take(&mut self.f);
-                (*exception).write::<U>(f(state, error));
+                let error = f(state, error);
+                (*exception).push::<U>(error);
+            } else {
+                ManuallyDrop::drop(&mut self.state);
+                ManuallyDrop::drop(&mut self.f);
             }
         })
     }
@@ -0,0 +1,68 @@
+use crate::{
+    iex_result::CallWithMarker,
+    imp::{IexResult, Marker},
+};
+
+/// The return type of [`#[iex(fn_ptr)]`](macro@crate::iex#iexfn_ptr) functions.
+///
+/// Unlike the opaque type returned by a plain `#[iex]` function, this type is nameable and only
+/// depends on the argument types `A` (as a tuple), the success type `T` and the error type `E`.
+/// Thus different functions with the same signature can be coerced to the same function pointer
+/// type:
+///
+/// ```
+/// use iex::{iex, FnOutcome, Outcome};
+///
+/// #[iex(fn_ptr)]
+/// fn increment(x: &mut i32) -> Result<(), &'static str> {
+///     *x = x.checked_add(1).ok_or("Overflow")?;
+///     Ok(())
+/// }
+///
+/// #[iex(fn_ptr)]
+/// fn double(x: &mut i32) -> Result<(), &'static str> {
+///     *x = x.checked_mul(2).ok_or("Overflow")?;
+///     Ok(())
+/// }
+///
+/// type Op = fn(&mut i32) -> FnOutcome<(&mut i32,), (), &'static str>;
+///
+/// const OPS: [Op; 2] = [increment, double];
+///
+/// #[iex]
+/// fn run(x: &mut i32, code: &[u8]) -> Result<(), &'static str> {
+///     for &opcode in code {
+///         OPS[opcode as usize](x)?;
+///     }
+///     Ok(())
+/// }
+///
+/// let mut x = 1;
+/// assert_eq!(run(&mut x, &[0, 1, 1]).into_result(), Ok(()));
+/// assert_eq!(x, 8);
+/// assert_eq!(run(&mut x, &[1; 32]).into_result(), Err("Overflow"));
+/// ```
+///
+/// Calling a function pointer only stores the arguments. The function is invoked when the outcome
+/// is consumed, e.g. by `?`, and it propagates errors by unwinding, just like a normal `#[iex]`
+/// function.
+pub type FnOutcome<A, T, E> = IexResult<T, E, FnCall<A, T, E>>;
+
+pub struct FnCall<A, T, E> {
+    args: A,
+    call: fn(A, Marker<E>) -> T,
+}
+
+impl<A, T, E> FnCall<A, T, E> {
+    #[inline(always)]
+    pub fn new(args: A, call: fn(A, Marker<E>) -> T) -> Self {
+        Self { args, call }
+    }
+}
+
+impl<A, T, E> CallWithMarker<T, E> for FnCall<A, T, E> {
+    #[inline(always)]
+    fn call_with_marker(self, marker: Marker<E>) -> T {
+        (self.call)(self.args, marker)
+    }
+}
@@ -0,0 +1,156 @@
+use crate::exception::{self, Scope};
+
+/// Check whether an error is being propagated on this thread.
+///
+/// Returns `true` if an error thrown by an [`#[iex]`](macro@crate::iex) function is unwinding the
+/// stack, and `false` otherwise, in particular when a genuine panic is. This is the iex counterpart
+/// of [`std::thread::panicking`], which returns `true` in both cases, and is useful in [`Drop`]
+/// implementations that need to tell them apart.
+///
+/// To run code only if a certain scope is left due to an error or normally, see [`on_error`] and
+/// [`on_success`].
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, Outcome};
+/// use std::cell::Cell;
+///
+/// struct Transaction<'a>(&'a Cell<&'static str>);
+///
+/// impl Drop for Transaction<'_> {
+///     fn drop(&mut self) {
+///         if iex::is_propagating() {
+///             self.0.set("rolled back");
+///         } else if !std::thread::panicking() {
+///             self.0.set("committed");
+///         }
+///     }
+/// }
+///
+/// #[iex]
+/// fn run(state: &Cell<&'static str>, fail: bool) -> Result<(), String> {
+///     let _transaction = Transaction(state);
+///     if fail {
+///         return Err("failed".to_string());
+///     }
+///     Ok(())
+/// }
+///
+/// let state = Cell::new("");
+/// run(&state, false).into_result().unwrap();
+/// assert_eq!(state.get(), "committed");
+/// run(&state, true).into_result().unwrap_err();
+/// assert_eq!(state.get(), "rolled back");
+/// ```
+pub fn is_propagating() -> bool {
+    exception::is_propagating_since(0)
+}
+
+/// A guard that runs a closure if its scope is left due to an error.
+///
+/// Created by [`on_error`].
+#[must_use = "if unused, the guard is dropped immediately"]
+pub struct OnError<F: FnOnce()> {
+    scope: Scope,
+    f: Option<F>,
+}
+
+/// A guard that runs a closure if its scope is left normally.
+///
+/// Created by [`on_success`].
+#[must_use = "if unused, the guard is dropped immediately"]
+pub struct OnSuccess<F: FnOnce()> {
+    scope: Scope,
+    f: Option<F>,
+}
+
+/// Run `f` when the returned guard is dropped due to error propagation.
+///
+/// The closure is called if an error thrown by an [`#[iex]`](macro@crate::iex) function after the
+/// guard was created unwinds the guard. It is not called if the guard is dropped normally or by a
+/// genuine panic.
+///
+/// `f` runs during unwinding, so it must not panic, as that aborts the process. It may call
+/// [`#[iex]`](macro@crate::iex) functions, as long as it handles their errors.
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, Outcome};
+/// use std::cell::RefCell;
+///
+/// #[iex]
+/// fn push_all(log: &RefCell<Vec<i32>>, values: &[i32]) -> Result<(), String> {
+///     let len = log.borrow().len();
+///     let _rollback = iex::on_error(|| log.borrow_mut().truncate(len));
+///     for &value in values {
+///         if value < 0 {
+///             return Err(format!("{value} is negative"));
+///         }
+///         log.borrow_mut().push(value);
+///     }
+///     Ok(())
+/// }
+///
+/// let log = RefCell::new(Vec::new());
+/// push_all(&log, &[1, 2]).into_result().unwrap();
+/// push_all(&log, &[3, -4, 5]).into_result().unwrap_err();
+/// assert_eq!(*log.borrow(), [1, 2]);
+/// ```
+pub fn on_error<F: FnOnce()>(f: F) -> OnError<F> {
+    OnError {
+        scope: Scope::enter(),
+        f: Some(f),
+    }
+}
+
+/// Run `f` when the returned guard is dropped normally.
+///
+/// The closure is called if the guard goes out of scope without unwinding, i.e. neither because of
+/// error propagation nor because of a panic.
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, Outcome};
+/// use std::cell::Cell;
+///
+/// #[iex]
+/// fn parse(committed: &Cell<bool>, s: &str) -> Result<i32, std::num::ParseIntError> {
+///     let _commit = iex::on_success(|| committed.set(true));
+///     Ok(s.parse::<i32>()?)
+/// }
+///
+/// let committed = Cell::new(false);
+/// parse(&committed, "x").into_result().unwrap_err();
+/// assert!(!committed.get());
+/// parse(&committed, "1").into_result().unwrap();
+/// assert!(committed.get());
+/// ```
+pub fn on_success<F: FnOnce()>(f: F) -> OnSuccess<F> {
+    OnSuccess {
+        scope: Scope::enter(),
+        f: Some(f),
+    }
+}
+
+impl<F: FnOnce()> Drop for OnError<F> {
+    fn drop(&mut self) {
+        if self.scope.is_propagating() {
+            if let Some(f) = self.f.take() {
+                f();
+            }
+        }
+    }
+}
+
+impl<F: FnOnce()> Drop for OnSuccess<F> {
+    fn drop(&mut self) {
+        if !self.scope.is_propagating() && !self.scope.is_panicking() {
+            if let Some(f) = self.f.take() {
+                f();
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{
+    closure::{RawFn, RawFnMut, RawFnOnce},
+    iex,
+    imp::IexResult,
+    native_try::impl_outcome,
+    IexClosure, Outcome,
+};
+use std::marker::PhantomData;
+
+/// An `#[iex]` callback that can be called once.
+///
+/// This is the `#[iex]` counterpart of [`FnOnce`]. Bounds like `impl FnOnce(A) -> Result<T, E>`
+/// can't accept `#[iex]` callbacks, and `impl FnOnce(A) -> impl Outcome<..>` is not valid Rust.
+/// Use `impl IexFnOnce<(A,), E, Output = T>` instead.
+///
+/// This trait is implemented for:
+///
+/// - Closures and functions that return an [`Outcome`], including `#[iex]` closures and
+///   functions, and regular ones that return a [`Result`],
+/// - [`IexClosure`]s created by [`iex::closure!`](crate::closure).
+///
+/// As with [`IexClosure`], the arguments are passed as a tuple.
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, IexFnMut, Outcome};
+///
+/// struct Node {
+///     value: i32,
+///     children: Vec<Node>,
+/// }
+///
+/// #[iex]
+/// fn walk<E>(node: &Node, cb: &mut impl IexFnMut<(i32,), E, Output = ()>) -> Result<(), E> {
+///     cb.iex_call_mut((node.value,))?;
+///     for child in &node.children {
+///         walk(child, cb)?;
+///     }
+///     Ok(())
+/// }
+///
+/// let tree = Node {
+///     value: 1,
+///     children: vec![
+///         Node { value: 2, children: Vec::new() },
+///         Node { value: -3, children: Vec::new() },
+///     ],
+/// };
+///
+/// let mut sum = 0;
+/// let mut add = iex::closure!(|value: i32| -> Result<(), String> {
+///     if value < 0 {
+///         return Err(format!("Negative value: {value}"));
+///     }
+///     sum += value;
+///     Ok(())
+/// });
+/// let result = walk(&tree, &mut add).into_result();
+/// assert_eq!(result, Err("Negative value: -3".to_string()));
+/// assert_eq!(sum, 3);
+/// ```
+pub trait IexFnOnce<Args, E> {
+    /// The success type of the callback.
+    type Output;
+
+    /// Call the callback by value.
+    #[iex]
+    fn iex_call_once(self, args: Args) -> Result<Self::Output, E>;
+}
+
+/// An `#[iex]` callback that can be called by mutable reference.
+///
+/// This is the `#[iex]` counterpart of [`FnMut`]. See [`IexFnOnce`] for more information.
+pub trait IexFnMut<Args, E>: IexFnOnce<Args, E> {
+    /// Call the callback by mutable reference.
+    #[iex]
+    fn iex_call_mut(&mut self, args: Args) -> Result<Self::Output, E>;
+}
+
+/// An `#[iex]` callback that can be called by reference.
+///
+/// This is the `#[iex]` counterpart of [`Fn`]. See [`IexFnOnce`] for more information.
+pub trait IexFn<Args, E>: IexFnMut<Args, E> {
+    /// Call the callback by reference.
+    #[iex]
+    fn iex_call(&self, args: Args) -> Result<Self::Output, E>;
+}
+
+macro_rules! impl_iex_fn {
+    ($($arg:ident)*) => {
+        #[allow(non_snake_case)]
+        impl<Func, R, E, $($arg,)*> IexFnOnce<($($arg,)*), E> for Func
+        where
+            Func: FnOnce($($arg,)*) -> R,
+            R: Outcome<Error = E>,
+        {
+            type Output = R::Output;
+
+            #[cfg(doc)]
+            #[crate::iex]
+            fn iex_call_once(self, args: ($($arg,)*)) -> Result<R::Output, E> {}
+
+            #[cfg(not(doc))]
+            #[inline(always)]
+            fn iex_call_once(
+                self,
+                ($($arg,)*): ($($arg,)*),
+            ) -> impl_outcome!(R::Output, E) {
+                IexResult(
+                    move |marker| self($($arg,)*).get_value_or_panic(marker),
+                    PhantomData,
+                )
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<Func, R, E, $($arg,)*> IexFnMut<($($arg,)*), E> for Func
+        where
+            Func: FnMut($($arg,)*) -> R,
+            R: Outcome<Error = E>,
+        {
+            #[cfg(doc)]
+            #[crate::iex]
+            fn iex_call_mut(&mut self, args: ($($arg,)*)) -> Result<R::Output, E> {}
+
+            #[cfg(not(doc))]
+            #[inline(always)]
+            fn iex_call_mut(
+                &mut self,
+                ($($arg,)*): ($($arg,)*),
+            ) -> impl_outcome!(R::Output, E) {
+                IexResult(
+                    move |marker| self($($arg,)*).get_value_or_panic(marker),
+                    PhantomData,
+                )
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<Func, R, E, $($arg,)*> IexFn<($($arg,)*), E> for Func
+        where
+            Func: Fn($($arg,)*) -> R,
+            R: Outcome<Error = E>,
+        {
+            #[cfg(doc)]
+            #[crate::iex]
+            fn iex_call(&self, args: ($($arg,)*)) -> Result<R::Output, E> {}
+
+            #[cfg(not(doc))]
+            #[inline(always)]
+            fn iex_call(
+                &self,
+                ($($arg,)*): ($($arg,)*),
+            ) -> impl_outcome!(R::Output, E) {
+                IexResult(
+                    move |marker| self($($arg,)*).get_value_or_panic(marker),
+                    PhantomData,
+                )
+            }
+        }
+    };
+}
+
+impl_iex_fn!();
+impl_iex_fn!(A1);
+impl_iex_fn!(A1 A2);
+impl_iex_fn!(A1 A2 A3);
+impl_iex_fn!(A1 A2 A3 A4);
+impl_iex_fn!(A1 A2 A3 A4 A5);
+impl_iex_fn!(A1 A2 A3 A4 A5 A6);
+impl_iex_fn!(A1 A2 A3 A4 A5 A6 A7);
+impl_iex_fn!(A1 A2 A3 A4 A5 A6 A7 A8);
+
+impl<F: RawFnOnce<A, E>, A, E> IexFnOnce<A, E> for IexClosure<F> {
+    type Output = F::Output;
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn iex_call_once(self, args: A) -> Result<F::Output, E> {}
+
+    #[cfg(not(doc))]
+    #[inline(always)]
+    fn iex_call_once(self, args: A) -> impl_outcome!(F::Output, E) {
+        self.call_once(args)
+    }
+}
+
+impl<F: RawFnMut<A, E>, A, E> IexFnMut<A, E> for IexClosure<F> {
+    #[cfg(doc)]
+    #[crate::iex]
+    fn iex_call_mut(&mut self, args: A) -> Result<F::Output, E> {}
+
+    #[cfg(not(doc))]
+    #[inline(always)]
+    fn iex_call_mut(&mut self, args: A) -> impl_outcome!(F::Output, E) {
+        self.call_mut(args)
+    }
+}
+
+impl<F: RawFn<A, E>, A, E> IexFn<A, E> for IexClosure<F> {
+    #[cfg(doc)]
+    #[crate::iex]
+    fn iex_call(&self, args: A) -> Result<F::Output, E> {}
+
+    #[cfg(not(doc))]
+    #[inline(always)]
+    fn iex_call(&self, args: A) -> impl_outcome!(F::Output, E) {
+        self.call(args)
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{exception, imp::Marker, Outcome};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub struct IexFuture<R: Outcome, Fut> {
+    future: Fut,
+    marker: Marker<R::Error>,
+    phantom: PhantomData<fn() -> R>,
+}
+
+impl<R: Outcome, Fut: Future<Output = R>> IexFuture<R, Fut> {
+    #[inline(always)]
+    pub fn new(f: impl FnOnce(Marker<R::Error>) -> Fut) -> Self {
+        // SAFETY: The future is only ever polled by `IexFuture::poll`, which catches the panic.
+        let marker = unsafe { Marker::new() };
+        Self {
+            future: f(marker),
+            marker,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<R: Outcome, Fut: Future<Output = R>> Future for IexFuture<R, Fut> {
+    type Output = Result<R::Output, R::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned: it is never moved out of `self`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let marker = this.marker;
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        // The error is raised and caught within a single call to `poll`, so it never outlives the
+        // thread it was stored on, even if the executor moves the task between threads.
+        let result = unsafe {
+            exception::catch(|| match future.poll(cx) {
+                Poll::Ready(outcome) => Poll::Ready(outcome.get_value_or_panic(marker)),
+                Poll::Pending => Poll::Pending,
+            })
+        };
+        match result {
+            Ok(poll) => poll.map(Ok),
+            Err(error) => Poll::Ready(Err(error)),
+        }
+    }
+}
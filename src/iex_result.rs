@@ -1,10 +1,11 @@
 use crate::{
+    exception,
     imp::{ExceptionMapper, Marker},
+    native_try::impl_outcome,
     outcome::Sealed,
-    IexPanic, Outcome, EXCEPTION,
+    Outcome,
 };
 use std::marker::PhantomData;
-use std::panic::AssertUnwindSafe;
 
 pub(crate) trait CallWithMarker<T, E> {
     fn call_with_marker(self, marker: Marker<E>) -> T;
@@ -38,14 +39,13 @@ impl<T, E, Func: CallWithMarker<T, E>> Outcome for IexResult<T, E, Func> {
     }
 
     #[cfg(not(doc))]
-    fn inspect_err<F>(self, f: F) -> impl Outcome<Output = T, Error = E>
+    fn inspect_err<F>(self, f: F) -> impl_outcome!(T, E)
     where
         F: FnOnce(&Self::Error),
     {
-        // NB: It is impossible to implement inspect_err without writeback that map_err
-        // performs. Indeed, if `f` calls an #[iex] function that returns an error, that error
-        // is saved to EXCEPTION. It is necessary to override it back with err before returning
-        // from `inspect_err(..).get_value_or_panic()`.
+        // `f` is called while the error is being propagated. If it calls #[iex] functions that
+        // fail, their errors are stacked on top of `err` and caught before `f` returns, so `err`
+        // is unaffected.
         self.map_err(|err| {
             f(&err);
             err
@@ -61,7 +61,7 @@ impl<T, E, Func: CallWithMarker<T, E>> Outcome for IexResult<T, E, Func> {
     }
 
     #[cfg(not(doc))]
-    fn map_err<F, O>(self, op: O) -> impl Outcome<Output = Self::Output, Error = F>
+    fn map_err<F, O>(self, op: O) -> impl_outcome!(Self::Output, F)
     where
         O: FnOnce(E) -> F,
     {
@@ -77,22 +77,7 @@ impl<T, E, Func: CallWithMarker<T, E>> Outcome for IexResult<T, E, Func> {
     }
 
     fn into_result(self) -> Result<T, E> {
-        std::panic::catch_unwind(AssertUnwindSafe(|| {
-            self.0.call_with_marker(unsafe { Marker::new() })
-        }))
-        .map_err(
-            #[cold]
-            |payload| {
-                if !payload.is::<IexPanic>() {
-                    std::panic::resume_unwind(payload);
-                }
-                EXCEPTION.with(|exception| unsafe {
-                    let exception = &mut *exception.get();
-                    let error = exception.read_unchecked();
-                    exception.clear();
-                    error
-                })
-            },
-        )
+        // SAFETY: The marker is only used within `catch`, which catches errors of type E.
+        unsafe { exception::catch(|| self.0.call_with_marker(Marker::new())) }
     }
 }
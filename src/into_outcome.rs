@@ -0,0 +1,114 @@
+use crate::{imp::Marker, native_try::impl_outcome, outcome::Sealed, Outcome};
+
+/// Conversion into an [`Outcome`].
+///
+/// [`Outcome`] itself is sealed, but result-like types can opt into `#[iex]` by implementing this
+/// trait. Every `IntoOutcome` type is an [`Outcome`] with the same success and error types as
+/// [`IntoOutcome`](Self::IntoOutcome), so it can be used as the declared return type of an
+/// [`#[iex]`](macro@crate::iex) function, as an operand of `?`, or returned directly.
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, IntoOutcome, Outcome};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum ParseError {
+///     Incomplete,
+///     Invalid(char),
+/// }
+///
+/// enum ParseResult<T> {
+///     Done(T),
+///     Incomplete,
+///     Invalid(char),
+/// }
+///
+/// impl<T> IntoOutcome for ParseResult<T> {
+///     type IntoOutcome = Result<T, ParseError>;
+///
+///     fn into_outcome(self) -> Self::IntoOutcome {
+///         match self {
+///             ParseResult::Done(value) => Ok(value),
+///             ParseResult::Incomplete => Err(ParseError::Incomplete),
+///             ParseResult::Invalid(c) => Err(ParseError::Invalid(c)),
+///         }
+///     }
+/// }
+///
+/// fn digit(s: &str) -> ParseResult<u32> {
+///     match s.chars().next() {
+///         None => ParseResult::Incomplete,
+///         Some(c) => match c.to_digit(10) {
+///             Some(digit) => ParseResult::Done(digit),
+///             None => ParseResult::Invalid(c),
+///         },
+///     }
+/// }
+///
+/// #[iex]
+/// fn two_digits(s: &str) -> ParseResult<u32> {
+///     let tens = digit(s)?;
+///     ParseResult::Done(tens * 10 + digit(&s[1..])?)
+/// }
+///
+/// assert_eq!(two_digits("42").into_result(), Ok(42));
+/// assert_eq!(two_digits("4").into_result(), Err(ParseError::Incomplete));
+/// assert_eq!(two_digits("4x").into_result(), Err(ParseError::Invalid('x')));
+/// ```
+pub trait IntoOutcome {
+    /// The outcome this type converts to.
+    type IntoOutcome: Outcome;
+
+    /// Convert to an outcome.
+    fn into_outcome(self) -> Self::IntoOutcome;
+}
+
+impl<R: IntoOutcome> Sealed for R {}
+
+impl<R: IntoOutcome> Outcome for R {
+    type Output = <R::IntoOutcome as Outcome>::Output;
+
+    type Error = <R::IntoOutcome as Outcome>::Error;
+
+    #[inline(always)]
+    fn get_value_or_panic(self, marker: Marker<Self::Error>) -> Self::Output {
+        self.into_outcome().get_value_or_panic(marker)
+    }
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn inspect_err<F>(self, f: F) -> Result<Self::Output, Self::Error>
+    where
+        F: FnOnce(&Self::Error),
+    {
+    }
+
+    #[cfg(not(doc))]
+    fn inspect_err<F>(self, f: F) -> impl_outcome!(Self::Output, Self::Error)
+    where
+        F: FnOnce(&Self::Error),
+    {
+        self.into_outcome().inspect_err(f)
+    }
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn map_err<F, O>(self, op: O) -> Result<Self::Output, F>
+    where
+        O: FnOnce(Self::Error) -> F,
+    {
+    }
+
+    #[cfg(not(doc))]
+    fn map_err<F, O>(self, op: O) -> impl_outcome!(Self::Output, F)
+    where
+        O: FnOnce(Self::Error) -> F,
+    {
+        self.into_outcome().map_err(op)
+    }
+
+    fn into_result(self) -> Result<Self::Output, Self::Error> {
+        self.into_outcome().into_result()
+    }
+}
@@ -125,35 +125,57 @@
 //! type implements [`Outcome`], so you can call methods like [`map_err`](Outcome::map_err), but
 //! other than that, you must immediately propagate the error via `?`.
 //!
-//! Alternatively, you can cast it to a [`Result`] via [`.into_result()`](Outcome::into_result).
-//! This is the only way to avoid immediate propagation.
+//! Alternatively, you can cast it to a [`Result`] via [`.into_result()`](Outcome::into_result), or
+//! recover from the error with methods like [`or_else`](Outcome::or_else) and
+//! [`unwrap_or`](Outcome::unwrap_or). These are the only ways to avoid immediate propagation.
 //!
 //! Doing anything else to the return value, e.g. storing it in a variable and using it later will
 //! not cause UB, but will not work the way you think either. If you want to swallow the error, use
 //! `let _ = func().into_result();` instead.
 //!
+//! In debug builds, or with the `checked` feature enabled, iex records the type and the throw
+//! location of each error, and panics with a descriptive message if an error is read as a wrong
+//! type, overwritten, or never caught, e.g. because its unwinding was swallowed by
+//! [`std::panic::catch_unwind`]. These checks are disabled in release builds by default. Code that
+//! catches panics around [`#[iex]`](macro@iex) code should use [`catch_unwind`], which lets errors
+//! pass through; see [`IexPanic`] and [`set_uncaught_handler`] for what happens otherwise.
+//!
+//! Errors are propagated by unwinding, so [`std::sync::Mutex`] and [`std::sync::RwLock`] guards
+//! held across `?` poison their locks when an error passes through. Use [`sync::Mutex`] and
+//! [`sync::RwLock`] instead, which are only poisoned by genuine panics. For the same reason,
+//! [`std::thread::panicking`] returns `true` while an error is propagated; use [`is_propagating`]
+//! to tell errors from panics, or [`on_error`] and [`on_success`] to run code on a specific path.
+//!
 //! Directly returning an `#[iex] Result` (obtained from a function call) from another
-//! [`#[iex]`](macro@iex) function also works, provided that it's the only `return` statement in the
-//! function. Use `Ok(..?)` if there are multiple returns.
+//! [`#[iex]`](macro@iex) function also works, and different `return` statements and branches can
+//! freely mix `#[iex] Result`s and [`Result`]s, as long as their error types match exactly.
 //!
 //! [`#[iex]`](macro@iex) works on methods. If applied to a function in an `impl Trait for Type`
 //! block, the corresponding function in the `trait Trait` block should also be marked with
 //! [`#[iex]`](macro@iex). Such traits are not object-safe, unless the method is restricted to
-//! `where Self: Sized` (open an issue if you want me to spend time developing a workaround).
+//! `where Self: Sized` or marked with
+//! [`#[iex(object_safe)]`](macro@iex#iexobject_safe) both in the `trait` and in the `impl`s.
 
 #![cfg_attr(doc, feature(doc_auto_cfg))]
+#![cfg_attr(feature = "nightly", feature(try_trait_v2))]
 
 mod macros;
-pub use macros::{iex, try_block};
+pub use macros::{closure, iex, try_block, try_macro};
 
 use std::cell::UnsafeCell;
 
 mod exception;
-use exception::Exception;
+use exception::ExceptionStack;
 
 mod outcome;
 pub use outcome::Outcome;
 
+mod into_outcome;
+pub use into_outcome::IntoOutcome;
+
+mod box_outcome;
+pub use box_outcome::{BoxOutcome, SendBoxOutcome};
+
 #[cfg(feature = "anyhow")]
 mod anyhow_compat;
 #[cfg(feature = "anyhow")]
@@ -166,32 +188,82 @@ impl<T, E> Context<T, E> for Result<T, E> {}
 #[cfg(not(feature = "anyhow"))]
 impl<T, E, Func: iex_result::CallWithMarker<T, E>> Context<T, E> for imp::IexResult<T, E, Func> {}
 #[cfg(not(feature = "anyhow"))]
-impl<T> Context<T, std::convert::Infallible> for Option<T> {}
+impl<T, E> Context<T, E> for BoxOutcome<'_, T, E> {}
+#[cfg(not(feature = "anyhow"))]
+impl<T, E> Context<T, E> for SendBoxOutcome<'_, T, E> {}
+#[cfg(not(feature = "anyhow"))]
+impl<T> Context<T, NoneError> for Option<T> {}
+#[cfg(not(feature = "anyhow"))]
+impl<T, E, R: IntoOutcome<IntoOutcome: Outcome<Output = T, Error = E>>> Context<T, E> for R {}
 
+mod iex_future;
 mod iex_result;
+mod option;
+pub use option::NoneError;
 mod result;
 
+mod fn_outcome;
+pub use fn_outcome::FnOutcome;
+
+mod closure;
+pub use closure::IexClosure;
+
+mod iex_fn;
+pub use iex_fn::{IexFn, IexFnMut, IexFnOnce};
+
+mod guard;
+pub use guard::{is_propagating, on_error, on_success, OnError, OnSuccess};
+
+mod boundary;
+mod dyn_call;
 mod exception_mapper;
 mod forward;
 mod marker;
+mod native_try;
+mod returns;
 
 pub mod example;
+pub mod raw;
+pub mod sync;
 
-struct IexPanic;
+mod unwind;
+pub use unwind::{catch_unwind, set_uncaught_handler, take_uncaught_handler, ErrorInfo, IexPanic};
 
 thread_local! {
-    static EXCEPTION: UnsafeCell<Exception> = const { UnsafeCell::new(Exception::new()) };
+    static EXCEPTION: UnsafeCell<ExceptionStack> = const { UnsafeCell::new(ExceptionStack::new()) };
 }
 
 #[doc(hidden)]
 pub mod imp {
     use super::*;
+    pub use boundary::FromResult;
+    pub use closure::{RawFn, RawFnMut, RawFnOnce};
+    pub use dyn_call::DynCall;
     pub use exception_mapper::ExceptionMapper;
     pub use fix_hidden_lifetime_bug;
+    pub use fn_outcome::FnCall;
     pub use forward::_IexForward;
+    pub use iex_future::IexFuture;
     pub use iex_result::IexResult;
     pub use marker::Marker;
+    #[cfg(feature = "nightly")]
+    pub use native_try::NativeTry;
+    pub use returns::_IexReturn;
     pub struct NoCopy;
+
+    // Glob-imported by #[iex::try_macro], so that importing it several times does not conflict.
+    // #[iex] bodies shadow it with a macro that forwards the error.
+    pub mod try_fallback {
+        pub use crate::__iex_try_fallback as __iex_try;
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __iex_try_fallback {
+    ($e:expr) => {
+        $e?
+    };
 }
 
 extern crate self as iex;
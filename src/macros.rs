@@ -1,6 +1,7 @@
 /// Use unwinding for error propagation.
 ///
-/// This attribute can be applied to functions and closures.
+/// This attribute can be applied to functions and closures, including `async` ones, and to `impl`
+/// blocks, traits and inline modules.
 ///
 /// Applying this attribute to a function or a closure that returns [`Result<T, E>`] turns it into a
 /// function/closure that returns `#[iex] Result<T, E>`. This is an opaque type, but it implements
@@ -11,7 +12,95 @@
 /// opposed to the built-in try operator) that propagates the error from a [`Result<T, E>`] or an
 /// `#[iex] Result<T, E>` and returns a `T`.
 ///
-/// **Closure support is incomplete and nightly-only.**
+/// `T` and `E` may contain `impl Trait`, e.g. `Result<impl Iterator<Item = u8>, E>`, just like in
+/// regular functions.
+///
+/// **Closure support is incomplete and nightly-only.** Use [`iex::closure!`](crate::closure) on
+/// stable.
+///
+/// # Async functions
+///
+/// `#[iex]` can be applied to `async fn`, in which case the function returns a future that resolves
+/// to an `#[iex] Result`. `?` works across `.await` points, both on regular [`Result`]s and on
+/// awaited `#[iex] Result`s:
+///
+/// ```
+/// use iex::{iex, Outcome};
+///
+/// #[iex]
+/// async fn parse(s: &str) -> Result<u32, String> {
+///     s.parse().map_err(|_| format!("Invalid number {s}"))
+/// }
+///
+/// #[iex]
+/// async fn sum(list: &str) -> Result<u32, String> {
+///     let mut sum = 0;
+///     for s in list.split(',') {
+///         sum += parse(s).await?;
+///     }
+///     Ok(sum)
+/// }
+/// ```
+///
+/// The outcome does not borrow from the arguments, so it can outlive them:
+///
+/// ```edition2024
+/// # use iex::{iex, Outcome};
+/// # #[iex]
+/// # async fn parse(s: &str) -> Result<u32, String> {
+/// #     s.parse().map_err(|_| format!("Invalid number {s}"))
+/// # }
+/// async fn parse_owned() -> Result<u32, String> {
+///     let outcome = {
+///         let s = String::from("123");
+///         parse(&s).await
+///     };
+///     outcome.into_result()
+/// }
+/// ```
+///
+/// The error is raised and caught within a single poll of the future returned by the outermost
+/// `#[iex] async fn`, so it is safe to move such futures between threads, e.g. in work-stealing
+/// executors.
+///
+/// This comes at a cost: each poll of an `#[iex] async fn` future is wrapped in a
+/// [`catch_unwind`](std::panic::catch_unwind), just like a call to
+/// [`into_result`](crate::Outcome::into_result). This is cheap when no error is thrown, but awaiting
+/// a failing future catches the error and `?` throws it again, so an error crossing several nested
+/// `#[iex] async fn`s is unwound once per function. Code that fails often and spends little time
+/// awaiting is better off in synchronous `#[iex]` functions.
+///
+/// Async closures are supported too, but they have to be `async move`, as they are desugared to
+/// `move |..| async move { .. }`.
+///
+/// # Impl blocks, traits and modules
+///
+/// Applying `#[iex]` to an `impl` block, a `trait` or an inline `mod` applies it to every function
+/// inside whose return type is spelled as `Result<..>` (including aliases like `io::Result<..>`).
+/// Nested `impl`s, `trait`s and `mod`s in a module are processed too. This keeps traits and their
+/// `impl`s consistent without marking each method by hand:
+///
+/// ```
+/// use iex::{iex, Outcome};
+///
+/// #[iex]
+/// trait Parse: Sized {
+///     fn parse(s: &str) -> Result<Self, String>;
+/// }
+///
+/// #[iex]
+/// impl Parse for u8 {
+///     fn parse(s: &str) -> Result<Self, String> {
+///         s.parse().map_err(|_| format!("Invalid number {s}"))
+///     }
+/// }
+///
+/// assert_eq!(u8::parse("12").into_result(), Ok(12));
+/// ```
+///
+/// Functions that are already marked with `#[iex(..)]` are left as is. Use `#[iex(skip)]` to keep a
+/// function returning an algebraic [`Result`]. `const fn`s are always skipped. Arguments passed to the outer `#[iex(..)]`, such as
+/// `object_safe`, are forwarded to every rewritten function.
 ///
 /// # Pitfalls
 ///
@@ -36,14 +125,27 @@
 /// ```
 ///
 /// This is the conventional way to specify elided lifetimes on structs, so it shouldn't be a
-/// nuisance.
+/// nuisance. `#[iex]` cannot see whether a type has lifetime parameters, so it can't add `'_` by
+/// itself, but it does point out the arguments that need it:
+///
+/// ```text
+/// error: hidden lifetime parameters in types are deprecated
+///  --> src/lib.rs:7:11
+///   |
+/// 7 | fn bad(a: A) -> Result<(), ()> { Ok(()) }
+///   |           ^ expected lifetime parameter
+///   |
+/// help: indicate the anonymous lifetime
+///   |
+/// 7 | fn bad(a: A<'_>) -> Result<(), ()> { Ok(()) }
+///   |            ++++
+/// ```
 ///
 /// Additionally, if an associated function captures the lifetime from the `impl` block that is not
 /// mentioned in its signature, this lifetime must be specified explicitly:
 ///
 /// ```
 /// use iex::iex;
-/// use std::marker::PhantomData;
 ///
 /// struct Ref<'a, T>(Option<&'a T>);
 ///
@@ -56,7 +158,9 @@
 /// }
 /// ```
 ///
-/// Don't waste time adding the capture clause everywhere, just look out for errors like this one:
+/// When `#[iex]` is applied to the whole `impl` block, all lifetimes of the block are listed
+/// automatically, so this is only necessary for individually marked functions. Don't waste time
+/// adding the capture clause everywhere, just look out for errors like this one:
 ///
 /// ```text
 /// error[E0700]: hidden type for `impl Outcome` captures lifetime that does not appear in bounds
@@ -72,26 +176,6 @@
 ///    | |_____^
 /// ```
 ///
-/// Finally, make sure to use the same lifetimes in `trait` and `impl`:
-///
-/// ```compile_fail
-/// use iex::iex;
-///
-/// trait Trait {
-///     #[iex]
-///     fn takes_str(s: &'static str) -> Result<(), ()>;
-/// }
-///
-/// impl Trait for () {
-///     // error[E0308]: method not compatible with trait
-///     // Use 's: &'static str' instead
-///     #[iex]
-///     fn takes_str(s: &str) -> Result<(), ()> {
-///         Ok(())
-///     }
-/// }
-/// ```
-///
 /// ## Closures
 ///
 /// `#[iex]` closures can't take arguments whose types contain non-`'static` lifetimes. Sorry. Also,
@@ -99,12 +183,19 @@
 /// [`stmt_expr_attributes`](https://github.com/rust-lang/rust/issues/15701) and
 /// [`proc_macro_hygiene`](https://github.com/rust-lang/rust/issues/54727) to be enabled.
 ///
+/// [`iex::closure!`](crate::closure) has neither of these limitations, at the cost of a different
+/// calling syntax.
+///
 /// ## `?` in macros
 ///
 /// `#[iex]` needs to replace the `?` operator with a custom implementation in the function body.
-/// This notably fails if the `?` is generated by a macro:
+/// `?` inside the arguments of standard macros that take expressions, such as `vec![f()?]`,
+/// `format!`, `write!` or `assert_eq!`, is rewritten on a best-effort basis. Arguments of other
+/// macros are left alone, as they might use `?` for something other than error propagation, e.g.
+/// in `quote!`. This also means that a `?` generated by a macro fails:
 ///
-/// ```compile_fail
+#[cfg_attr(not(feature = "nightly"), doc = "```compile_fail")]
+#[cfg_attr(feature = "nightly", doc = "```")]
 /// use iex::iex;
 ///
 /// #[iex]
@@ -123,6 +214,14 @@
 /// }
 /// ```
 ///
+/// Mark such macros with [`#[iex::try_macro]`](macro@crate::try_macro) to fix this. This applies to
+/// `macro_rules!` defined in the function body too.
+///
+/// Alternatively, enable the `nightly` feature of this crate. `#[iex] Result`s then implement the
+/// unstable [`Try`](core::ops::Try) trait, so the built-in `?` works on them wherever `#[iex]`
+/// can't rewrite it: in such macros, in functions returning a [`Result`], and in `try` blocks. The
+/// built-in `?` catches the error and rethrows it, so it's slower than the rewritten one.
+///
 /// # Attributes
 ///
 /// Rust evaluates attribute macros from top to bottom, so if `#[iex]` is not the only attribute
@@ -147,6 +246,122 @@
 /// This use is specific for `map_err` and `inspect_err`. See the documentation for
 /// [`Outcome`](crate::Outcome::map_err) for more information.
 ///
+/// # `#[iex(object_safe)]`
+///
+/// By default, a trait containing an `#[iex]` method is not object-safe, because the method returns
+/// an opaque type. Marking the method with `#[iex(object_safe)]` both in the `trait` and in all the
+/// `impl`s makes it callable on trait objects:
+///
+/// ```
+/// use iex::{iex, Outcome};
+///
+/// trait Decoder {
+///     #[iex(object_safe)]
+///     fn decode(&self, input: &[u8]) -> Result<u8, String>;
+/// }
+///
+/// struct Digit;
+///
+/// impl Decoder for Digit {
+///     #[iex(object_safe)]
+///     fn decode(&self, input: &[u8]) -> Result<u8, String> {
+///         match input {
+///             [c @ b'0'..=b'9'] => Ok(c - b'0'),
+///             _ => Err(format!("Not a digit: {input:?}")),
+///         }
+///     }
+/// }
+///
+/// #[iex]
+/// fn decode_all(decoders: &[Box<dyn Decoder>], input: &[u8]) -> Result<Vec<u8>, String> {
+///     let mut results = Vec::new();
+///     for decoder in decoders {
+///         results.push(decoder.decode(input)?);
+///     }
+///     Ok(results)
+/// }
+///
+/// assert_eq!(decode_all(&[Box::new(Digit)], b"1").into_result(), Ok(vec![1]));
+/// ```
+///
+/// The `impl`s implement a hidden method that is stored in the vtable, and the trait provides the
+/// public method, which calls the hidden method lazily. Errors are still propagated by unwinding,
+/// even across dynamic calls.
+///
+/// Such methods must take `&self` or `&mut self` and must not be generic over types. The trait may
+/// provide a default implementation, and the `impl`s that override it must use
+/// `#[iex(object_safe)]` instead of implementing the method by hand.
+/// Argument types with hidden lifetime parameters must be written with `'_`, e.g. `Wrapper<'_>`
+/// instead of `Wrapper`.
+///
+/// # `#[iex(fn_ptr)]`
+///
+/// `#[iex]` functions return opaque types, so two `#[iex]` functions with the same signature can't
+/// be coerced to a common function pointer type. `#[iex(fn_ptr)]` makes the function return
+/// [`FnOutcome`](crate::FnOutcome) instead, which only depends on the argument types, the success
+/// type and the error type:
+///
+/// ```
+/// use iex::{iex, FnOutcome, Outcome};
+///
+/// #[iex(fn_ptr)]
+/// fn first(s: &str) -> Result<char, &'static str> {
+///     s.chars().next().ok_or("Empty string")
+/// }
+///
+/// #[iex(fn_ptr)]
+/// fn last(s: &str) -> Result<char, &'static str> {
+///     s.chars().next_back().ok_or("Empty string")
+/// }
+///
+/// let handlers: [fn(&str) -> FnOutcome<(&str,), char, &'static str>; 2] = [first, last];
+/// assert_eq!(handlers[1]("abc").into_result(), Ok('c'));
+/// ```
+///
+/// Such functions are otherwise used just like normal `#[iex]` functions, and calls via function
+/// pointers propagate errors by unwinding too. The argument types must be nameable, so
+/// `impl Trait` arguments are not supported, and hidden lifetime parameters must be written with
+/// `'_`, e.g. `Wrapper<'_>` instead of `Wrapper`, if there are other lifetimes in the arguments.
+///
+/// # `#[iex(boundary)]`
+///
+/// Trait methods like [`FromStr::from_str`](std::str::FromStr::from_str) or
+/// [`Iterator::next`] must return a real [`Result`] or [`Option`]. `#[iex(boundary)]` keeps the
+/// declared signature, but compiles the body like that of an `#[iex]` function, converting the
+/// outcome to the declared type once, when the function returns:
+///
+/// ```
+/// use iex::iex;
+/// use std::str::FromStr;
+///
+/// #[iex]
+/// fn parse_coordinate(s: &str) -> Result<i32, String> {
+///     s.parse().map_err(|_| format!("Invalid coordinate: {s}"))
+/// }
+///
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// impl FromStr for Point {
+///     type Err = String;
+///
+///     #[iex(boundary)]
+///     fn from_str(s: &str) -> Result<Self, String> {
+///         let (x, y) = s.split_once(',').ok_or("Missing comma")?;
+///         Ok(Point {
+///             x: parse_coordinate(x)?,
+///             y: parse_coordinate(y)?,
+///         })
+///     }
+/// }
+///
+/// assert!("1,2".parse::<Point>().is_ok());
+/// ```
+///
+/// `#[iex(boundary)]` can also be applied to a whole `impl` block, like `#[iex]`.
+///
 /// # Example
 ///
 /// ```
@@ -185,17 +400,28 @@
 /// }
 /// ```
 ///
-/// This attribute can only be applied to functions that return a [`Result`]:
+/// Functions returning an [`Option`] are supported too. `None` is propagated just like an error,
+/// and [`into_option`](crate::Outcome::into_option) converts the outcome back to an [`Option`]:
+///
+/// ```
+/// use iex::{iex, Outcome};
+/// use std::collections::HashMap;
 ///
-/// ```compile_fail
-/// # use iex::iex;
-/// // the trait `Outcome` is not implemented for `Option<()>`
 /// #[iex]
-/// fn invalid_example() -> Option<()> {
-///     None
+/// fn resolve(symbols: &HashMap<&str, &str>, name: &str) -> Option<usize> {
+///     let target = symbols.get(name)?;
+///     Some(target.len())
 /// }
+///
+/// let symbols = HashMap::from([("main", "0x1000")]);
+/// assert_eq!(resolve(&symbols, "main").into_option(), Some(6));
+/// assert_eq!(resolve(&symbols, "start").into_option(), None);
 /// ```
 ///
+/// Custom result-like types can be supported by implementing [`IntoOutcome`](crate::IntoOutcome).
+/// Other than that, this attribute can only be applied to functions that return a [`Result`] or an
+/// [`Option`]:
+///
 /// ```compile_fail
 /// # use iex::iex;
 /// // the trait `Outcome` is not implemented for `()`
@@ -204,6 +430,46 @@
 /// ```
 pub use iex_derive::iex;
 
+/// `#[iex]` closure that works on stable Rust.
+///
+/// `iex::closure!(|args| -> Result<T, E> { .. })` creates an [`IexClosure`](crate::IexClosure),
+/// which is called with a tuple of arguments via [`call`](crate::IexClosure::call),
+/// [`call_mut`](crate::IexClosure::call_mut) or [`call_once`](crate::IexClosure::call_once),
+/// depending on whether the closure behaves like [`Fn`], [`FnMut`] or [`FnOnce`]. The call returns
+/// an `#[iex] Result<T, E>`.
+///
+/// Unlike [`#[iex]`](macro@crate::iex) closures, this macro does not require nightly features, and
+/// the arguments can borrow data with any lifetime, as long as the returned value does not borrow
+/// from them. Arguments with lifetimes need explicit type annotations, just like with regular
+/// closures.
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, Outcome};
+///
+/// #[iex]
+/// fn parse_all(words: &[&str]) -> Result<Vec<u32>, String> {
+///     let mut parsed = 0;
+///     let mut parse = iex::closure!(|word: &str| -> Result<u32, String> {
+///         parsed += 1;
+///         word.parse().map_err(|_| format!("Invalid number: {word}"))
+///     });
+///     let mut numbers = Vec::new();
+///     for &word in words {
+///         numbers.push(parse.call_mut((word,))?);
+///     }
+///     assert_eq!(parsed, words.len());
+///     Ok(numbers)
+/// }
+///
+/// assert_eq!(parse_all(&["1", "2"]).into_result(), Ok(vec![1, 2]));
+/// assert_eq!(parse_all(&["1", "x"]).into_result(), Err("Invalid number: x".to_string()));
+/// ```
+///
+/// At most 8 arguments are supported.
+pub use iex_derive::closure;
+
 /// Try block.
 ///
 /// This is an implementation of the [nightly `try` blocks][1] for [`#[iex]`](macro@crate::iex).
@@ -232,3 +498,54 @@ pub use iex_derive::iex;
 ///
 /// [1]: https://doc.rust-lang.org/nightly/unstable-book/language-features/try-blocks.html
 pub use iex_derive::try_block;
+
+/// Support `?` in a `macro_rules!` macro used inside `#[iex]` functions.
+///
+/// [`#[iex]`](macro@crate::iex) cannot see the `?` operators generated by macros defined outside
+/// the `#[iex]` function. Applying this attribute to a `macro_rules!` definition makes the `?`s in
+/// its expansion propagate errors the `#[iex]` way when the macro is used inside an `#[iex]`
+/// function, and act as the built-in `?` elsewhere. The macro itself does not need to be changed.
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, Outcome};
+///
+/// #[iex]
+/// fn parse_digit(c: u8) -> Result<u8, String> {
+///     match c {
+///         b'0'..=b'9' => Ok(c - b'0'),
+///         _ => Err(format!("Not a digit: {}", c as char)),
+///     }
+/// }
+///
+/// #[iex::try_macro]
+/// macro_rules! read_digit {
+///     ($input:expr) => {
+///         parse_digit(*$input.next().ok_or("Unexpected end of input")?)?
+///     };
+/// }
+///
+/// #[iex]
+/// fn parse_pair(input: &[u8]) -> Result<(u8, u8), String> {
+///     let mut input = input.iter();
+///     Ok((read_digit!(input), read_digit!(input)))
+/// }
+///
+/// assert_eq!(parse_pair(b"12").into_result(), Ok((1, 2)));
+/// assert_eq!(parse_pair(b"1").into_result(), Err("Unexpected end of input".to_string()));
+/// ```
+///
+/// # Limitations
+///
+/// The `?`s are found by scanning the tokens of the macro, which is best-effort: `?` must follow a
+/// path, a call, a method call, a field access or an indexing expression, possibly in parentheses,
+/// e.g. `f(x)?`, `$e?` or `(a + b)?`. As in `#[iex]` functions, `?` inside the arguments of other
+/// macros is only rewritten for standard macros that take expressions, such as `vec!` or `format!`.
+///
+/// Outside `#[iex]` functions, the macro can only be used in the module it is defined in.
+///
+/// Inside an `#[iex]` function, the macro propagates errors out of the whole function, even if it
+/// is used inside a regular closure or a nested function item, which usually results in a compile
+/// error.
+pub use iex_derive::try_macro;
@@ -1,6 +1,20 @@
 use std::marker::PhantomData;
 
-pub struct Marker<E>(PhantomData<E>);
+/// A token used to propagate errors of type `E`.
+///
+/// See [`iex::raw`](crate::raw) for more information.
+///
+/// `Marker` is invariant in `E`, because the consumer catches errors of exactly that type. In
+/// particular, the lifetimes of the error cannot be shortened:
+///
+/// ```compile_fail
+/// use iex::raw::Marker;
+///
+/// fn shorten<'a>(marker: Marker<&'static str>) -> Marker<&'a str> {
+///     marker
+/// }
+/// ```
+pub struct Marker<E>(PhantomData<fn(E) -> E>);
 
 impl<E> Marker<E> {
     pub(crate) unsafe fn new() -> Self {
@@ -0,0 +1,77 @@
+// Added as a bound to the opaque types returned by #[iex] functions with the `nightly` feature, so
+// that they expose the `Try` implementation of the underlying type.
+
+#[cfg(feature = "nightly")]
+use crate::{iex_result::CallWithMarker, imp::IexResult, Outcome};
+#[cfg(feature = "nightly")]
+use std::{
+    convert::Infallible,
+    ops::{ControlFlow, FromResidual, Try},
+};
+
+#[cfg(feature = "nightly")]
+pub trait NativeTry<T, E>: Try<Output = T, Residual = Result<Infallible, E>> {}
+
+#[cfg(feature = "nightly")]
+impl<T, E, R: Try<Output = T, Residual = Result<Infallible, E>>> NativeTry<T, E> for R {}
+
+// `impl Outcome<Output = $output, Error = $error>`, bounded by `NativeTry` with the `nightly`
+// feature. Additional bounds can be passed after a semicolon.
+#[cfg(feature = "nightly")]
+macro_rules! impl_outcome {
+    ($output:ty, $error:ty $(; $($bounds:tt)*)?) => {
+        impl $crate::Outcome<Output = $output, Error = $error>
+            + $crate::imp::NativeTry<$output, $error>
+            $($($bounds)*)?
+    };
+}
+
+#[cfg(not(feature = "nightly"))]
+macro_rules! impl_outcome {
+    ($output:ty, $error:ty $(; $($bounds:tt)*)?) => {
+        impl $crate::Outcome<Output = $output, Error = $error> $($($bounds)*)?
+    };
+}
+
+pub(crate) use impl_outcome;
+
+// `#[iex] Result`s can only be consumed by `?`, not produced by `try` blocks. This fails the build
+// if `from_output` or `from_residual` are ever instantiated.
+#[cfg(feature = "nightly")]
+struct CannotConstruct<Func>(Func);
+
+#[cfg(feature = "nightly")]
+impl<Func> CannotConstruct<Func> {
+    const ERROR: () = panic!("#[iex] Result cannot be constructed via the Try trait");
+}
+
+#[cfg(feature = "nightly")]
+impl<T, E, Func: CallWithMarker<T, E>> Try for IexResult<T, E, Func> {
+    type Output = T;
+    type Residual = Result<Infallible, E>;
+
+    fn from_output(_output: T) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = CannotConstruct::<Func>::ERROR;
+        unreachable!()
+    }
+
+    #[inline(always)]
+    fn branch(self) -> ControlFlow<Result<Infallible, E>, T> {
+        match self.into_result() {
+            Ok(value) => ControlFlow::Continue(value),
+            Err(error) => ControlFlow::Break(Err(error)),
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T, E, Func: CallWithMarker<T, E>> FromResidual<Result<Infallible, E>>
+    for IexResult<T, E, Func>
+{
+    fn from_residual(_residual: Result<Infallible, E>) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = CannotConstruct::<Func>::ERROR;
+        unreachable!()
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{exception, imp::Marker, native_try::impl_outcome, outcome::Sealed, Outcome};
+
+/// The error type of [`Option`] when used as an [`Outcome`].
+///
+/// [`#[iex]`](macro@crate::iex) functions returning `Option<T>` return an `#[iex] Result<T,
+/// NoneError>` instead. Use [`into_option`](Outcome::into_option) to get the [`Option`] back.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NoneError;
+
+impl std::fmt::Display for NoneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("value is None")
+    }
+}
+
+impl std::error::Error for NoneError {}
+
+impl<T> Sealed for Option<T> {}
+
+impl<T> Outcome for Option<T> {
+    type Output = T;
+
+    type Error = NoneError;
+
+    #[cfg_attr(any(debug_assertions, feature = "checked"), track_caller)]
+    fn get_value_or_panic(self, _marker: Marker<NoneError>) -> T {
+        match self {
+            Some(value) => value,
+            None => exception::throw(NoneError),
+        }
+    }
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn inspect_err<F>(self, f: F) -> Result<T, NoneError>
+    where
+        F: FnOnce(&Self::Error),
+    {
+    }
+
+    #[cfg(not(doc))]
+    fn inspect_err<F>(self, f: F) -> impl_outcome!(T, NoneError)
+    where
+        F: FnOnce(&Self::Error),
+    {
+        self.ok_or(NoneError).inspect_err(f)
+    }
+
+    #[cfg(doc)]
+    #[crate::iex]
+    fn map_err<F, O>(self, op: O) -> Result<T, F>
+    where
+        O: FnOnce(NoneError) -> F,
+    {
+    }
+
+    #[cfg(not(doc))]
+    fn map_err<F, O>(self, op: O) -> impl_outcome!(Self::Output, F)
+    where
+        O: FnOnce(NoneError) -> F,
+    {
+        self.ok_or(NoneError).map_err(op)
+    }
+
+    fn into_result(self) -> Result<T, NoneError> {
+        self.ok_or(NoneError)
+    }
+}
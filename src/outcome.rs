@@ -1,10 +1,11 @@
-use crate::{iex, imp::Marker};
+use crate::{iex, imp::Marker, NoneError};
 
 pub trait Sealed {}
 
 /// Properties of a generalized result type.
 ///
-/// This unifies [`Result`] and `#[iex] Result`.
+/// This unifies [`Result`] and `#[iex] Result`. This trait is sealed, but custom types can
+/// implement [`IntoOutcome`](crate::IntoOutcome) to become outcomes.
 ///
 /// # Ownership
 ///
@@ -143,6 +144,221 @@ pub trait Outcome: Sealed + crate::Context<Self::Output, Self::Error> {
     where
         O: FnOnce(Self::Error) -> F;
 
+    /// Apply a function to the `Ok` value, leaving `Err` untouched.
+    ///
+    /// This is a generalized version of [`Result::map`]. Like all methods that return an
+    /// `#[iex] Result`, it's lazy: `op` is called when the result is consumed, e.g. by `?`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iex::{iex, Outcome};
+    ///
+    /// #[iex]
+    /// fn read_line() -> Result<String, std::io::Error> {
+    ///     Ok("Hello, world!\n".to_string())
+    /// }
+    ///
+    /// #[iex]
+    /// fn line_length() -> Result<usize, std::io::Error> {
+    ///     read_line().map(|line| line.trim_end().len())
+    /// }
+    ///
+    /// assert_eq!(line_length().into_result().unwrap(), 13);
+    /// ```
+    #[iex]
+    fn map<U, F>(self, op: F) -> Result<U, Self::Error>
+    where
+        Self: Sized,
+        F: FnOnce(Self::Output) -> U,
+    {
+        Ok(op(self?))
+    }
+
+    /// Calls a function with a reference to the contained value if `Ok`.
+    ///
+    /// Returns the original result.
+    ///
+    /// This is a generalized version of [`Result::inspect`].
+    #[iex]
+    fn inspect<F>(self, f: F) -> Result<Self::Output, Self::Error>
+    where
+        Self: Sized,
+        F: FnOnce(&Self::Output),
+    {
+        let value = self?;
+        f(&value);
+        Ok(value)
+    }
+
+    /// Calls `op` if the result is `Ok`, otherwise returns the `Err` value.
+    ///
+    /// This is a generalized version of [`Result::and_then`]. `op` may return either a [`Result`]
+    /// or an `#[iex] Result`, so it can be an `#[iex]` function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iex::{iex, Outcome};
+    ///
+    /// #[iex]
+    /// fn parse(s: &str) -> Result<u32, String> {
+    ///     s.parse().map_err(|_| format!("Invalid number: {s}"))
+    /// }
+    ///
+    /// #[iex]
+    /// fn reciprocal(x: u32) -> Result<f64, String> {
+    ///     if x == 0 {
+    ///         Err("Division by zero".to_string())
+    ///     } else {
+    ///         Ok(1.0 / x as f64)
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(parse("4").and_then(reciprocal).into_result(), Ok(0.25));
+    /// assert_eq!(
+    ///     parse("0").and_then(reciprocal).into_result(),
+    ///     Err("Division by zero".to_string()),
+    /// );
+    /// ```
+    #[iex]
+    fn and_then<U, F, R>(self, op: F) -> Result<U, Self::Error>
+    where
+        Self: Sized,
+        F: FnOnce(Self::Output) -> R,
+        R: Outcome<Output = U, Error = Self::Error>,
+    {
+        op(self?)
+    }
+
+    /// Converts the result to an [`Option`], discarding the error, if any.
+    ///
+    /// This is a generalized version of [`Result::ok`]. Like [`into_result`](Self::into_result),
+    /// it catches the error, so avoid it in the hot path.
+    fn ok(self) -> Option<Self::Output>
+    where
+        Self: Sized,
+    {
+        self.into_result().ok()
+    }
+
+    /// Converts the result to an [`Option`], discarding the success value, if any.
+    ///
+    /// This is a generalized version of [`Result::err`]. Like [`into_result`](Self::into_result),
+    /// it catches the error, so avoid it in the hot path.
+    fn err(self) -> Option<Self::Error>
+    where
+        Self: Sized,
+    {
+        self.into_result().err()
+    }
+
+    /// Calls `op` if the result is `Err`, otherwise returns the `Ok` value.
+    ///
+    /// This is a generalized version of [`Result::or_else`]. `op` may return either a [`Result`]
+    /// or an `#[iex] Result`, so it can be an `#[iex]` function.
+    ///
+    /// Only the error of this outcome is caught. Errors raised by `op` and panics that are not
+    /// errors are propagated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iex::{iex, Outcome};
+    ///
+    /// #[iex]
+    /// fn read_config() -> Result<String, std::io::Error> {
+    ///     Err(std::io::ErrorKind::NotFound.into())
+    /// }
+    ///
+    /// #[iex]
+    /// fn default_config() -> Result<String, String> {
+    ///     Ok("verbose = false".to_string())
+    /// }
+    ///
+    /// #[iex]
+    /// fn config() -> Result<String, String> {
+    ///     read_config().or_else(|_| default_config())
+    /// }
+    ///
+    /// assert_eq!(config().into_result(), Ok("verbose = false".to_string()));
+    /// ```
+    #[iex]
+    fn or_else<F, O, R>(self, op: O) -> Result<Self::Output, F>
+    where
+        Self: Sized,
+        O: FnOnce(Self::Error) -> R,
+        R: Outcome<Output = Self::Output, Error = F>,
+    {
+        match self.into_result() {
+            Ok(value) => Ok(value),
+            Err(err) => op(err),
+        }
+    }
+
+    /// Returns the contained `Ok` value or a provided default.
+    ///
+    /// This is a generalized version of [`Result::unwrap_or`]. Only the error of this outcome is
+    /// caught, other panics are propagated.
+    fn unwrap_or(self, default: Self::Output) -> Self::Output
+    where
+        Self: Sized,
+    {
+        self.into_result().unwrap_or(default)
+    }
+
+    /// Returns the contained `Ok` value or computes it from the error.
+    ///
+    /// This is a generalized version of [`Result::unwrap_or_else`]. Only the error of this outcome
+    /// is caught, other panics are propagated.
+    fn unwrap_or_else<O>(self, op: O) -> Self::Output
+    where
+        Self: Sized,
+        O: FnOnce(Self::Error) -> Self::Output,
+    {
+        self.into_result().unwrap_or_else(op)
+    }
+
+    /// Returns the contained `Ok` value or a default.
+    ///
+    /// This is a generalized version of [`Result::unwrap_or_default`]. Only the error of this
+    /// outcome is caught, other panics are propagated.
+    fn unwrap_or_default(self) -> Self::Output
+    where
+        Self: Sized,
+        Self::Output: Default,
+    {
+        self.into_result().unwrap_or_default()
+    }
+
+    /// Cast an `#[iex] Option` to an [`Option`].
+    ///
+    /// This is the counterpart of [`into_result`](Self::into_result) for
+    /// [`#[iex]`](macro@crate::iex) functions that return [`Option`], and has the same performance
+    /// considerations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use iex::{iex, Outcome};
+    ///
+    /// #[iex]
+    /// fn second_word(s: &str) -> Option<&str> {
+    ///     let mut words = s.split_whitespace();
+    ///     words.next()?;
+    ///     words.next()
+    /// }
+    ///
+    /// assert_eq!(second_word("hello world").into_option(), Some("world"));
+    /// assert_eq!(second_word("hello").into_option(), None);
+    /// ```
+    fn into_option(self) -> Option<Self::Output>
+    where
+        Self: Sized + Outcome<Error = NoneError>,
+    {
+        self.into_result().ok()
+    }
+
     /// Cast a generic result to a [`Result`].
     ///
     /// The [`Result`] can then be matched on, returned from a function that doesn't use
@@ -0,0 +1,114 @@
+//! Low-level API for writing `#[iex]` functions by hand.
+//!
+//! [`#[iex]`](macro@crate::iex) is a thin layer over this module. It can be used by code generators
+//! and other code that can't or doesn't want to use proc macros.
+//!
+//! An `#[iex]` function is a function that returns an outcome, which is a lazy computation taking a
+//! [`Marker`]. The outcome is run when it's consumed, e.g. by `?` or by
+//! [`into_result`](crate::Outcome::into_result), and the consumer supplies the marker. The marker
+//! is proof that the consumer will catch errors of type `E` thrown during the computation.
+//!
+//! ```
+//! use iex::{raw, Outcome};
+//!
+//! // #[iex]
+//! // fn checked_divide(a: u32, b: u32) -> Result<u32, &'static str> {
+//! //     a.checked_div(b).ok_or("Cannot divide by zero")
+//! // }
+//! fn checked_divide(
+//!     a: u32,
+//!     b: u32,
+//! ) -> impl Outcome<Output = u32, Error = &'static str> {
+//!     raw::outcome(move |marker| unsafe {
+//!         raw::forward(a.checked_div(b).ok_or("Cannot divide by zero"), marker)
+//!     })
+//! }
+//!
+//! // #[iex]
+//! // fn average(values: &[u32]) -> Result<u32, String> {
+//! //     let count = values.len() as u32;
+//! //     let sum = values.iter().sum();
+//! //     Ok(checked_divide(sum, count).map_err(|err| format!("No values: {err}"))?)
+//! // }
+//! fn average(values: &[u32]) -> impl Outcome<Output = u32, Error = String> + '_ {
+//!     raw::outcome(move |marker| unsafe {
+//!         let count = values.len() as u32;
+//!         let sum = values.iter().sum();
+//!         raw::map_exception(
+//!             marker,
+//!             |marker| raw::forward(checked_divide(sum, count), marker),
+//!             |err| format!("No values: {err}"),
+//!         )
+//!     })
+//! }
+//!
+//! assert_eq!(average(&[1, 2, 3]).into_result(), Ok(2));
+//! assert_eq!(
+//!     average(&[]).into_result(),
+//!     Err("No values: Cannot divide by zero".to_string()),
+//! );
+//! ```
+//!
+//! # Safety
+//!
+//! A marker is only valid during the call of the closure it was passed to. Throwing an error with
+//! a marker outside that call, e.g. after storing the marker somewhere, is undefined behavior,
+//! because the error might then be caught by a consumer expecting a different error type. This is
+//! why the functions that use markers are `unsafe`.
+//!
+//! For the same reason, the error thrown with a marker must be of exactly the type the marker was
+//! created for, including lifetimes. [`Marker`] is invariant, so this is checked by the compiler.
+
+use crate::{
+    imp::{ExceptionMapper, IexResult},
+    native_try::impl_outcome,
+    Outcome,
+};
+use std::marker::PhantomData;
+
+pub use crate::marker::Marker;
+
+/// Create an outcome from a computation that takes a marker.
+///
+/// `f` is called when the outcome is consumed. It may use the marker to propagate errors with
+/// [`forward`] and [`map_exception`] during the call.
+#[inline(always)]
+pub fn outcome<T, E, F: FnOnce(Marker<E>) -> T>(f: F) -> impl_outcome!(T, E) {
+    IexResult(f, PhantomData)
+}
+
+/// Get the success value of an outcome, throwing the error, if any.
+///
+/// This is what `?` compiles to in `#[iex]` functions when the error types match. If the outcome
+/// is lazy, it's run with `marker`.
+///
+/// # Safety
+///
+/// `marker` must have been passed to a closure that is currently running, and the call must
+/// happen on the same thread. See [module-level documentation](self#safety).
+#[inline(always)]
+#[cfg_attr(any(debug_assertions, feature = "checked"), track_caller)]
+pub unsafe fn forward<R: Outcome>(outcome: R, marker: Marker<R::Error>) -> R::Output {
+    outcome.get_value_or_panic(marker)
+}
+
+/// Run a computation, converting errors it throws with `op`.
+///
+/// `f` is called with a marker for errors of type `E`. If `f` throws an error, the error is passed
+/// to `op`, and the result is rethrown as if with `marker`. Otherwise, the return value of `f` is
+/// returned.
+///
+/// # Safety
+///
+/// `marker` must satisfy the requirements of [`forward`].
+#[inline(always)]
+pub unsafe fn map_exception<T, E, F>(
+    marker: Marker<F>,
+    f: impl FnOnce(Marker<E>) -> T,
+    op: impl FnOnce(E) -> F,
+) -> T {
+    let exception_mapper = ExceptionMapper::new(marker, (), |(), err| op(err));
+    let value = f(exception_mapper.get_in_marker());
+    exception_mapper.swallow();
+    value
+}
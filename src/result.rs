@@ -1,4 +1,4 @@
-use crate::{imp::Marker, outcome::Sealed, IexPanic, Outcome, EXCEPTION};
+use crate::{exception, imp::Marker, native_try::impl_outcome, outcome::Sealed, Outcome};
 
 impl<T, E> Sealed for Result<T, E> {}
 
@@ -7,12 +7,12 @@ impl<T, E> Outcome for Result<T, E> {
 
     type Error = E;
 
+    #[cfg_attr(any(debug_assertions, feature = "checked"), track_caller)]
     fn get_value_or_panic(self, _marker: Marker<E>) -> T {
-        self.unwrap_or_else(|error| {
-            EXCEPTION.with(|exception| unsafe { &mut *exception.get() }.write(error));
-            // This does not allocate, because IexPanic is a ZST.
-            std::panic::resume_unwind(Box::new(IexPanic))
-        })
+        match self {
+            Ok(value) => value,
+            Err(error) => exception::throw(error),
+        }
     }
 
     #[cfg(doc)]
@@ -24,7 +24,7 @@ impl<T, E> Outcome for Result<T, E> {
     }
 
     #[cfg(not(doc))]
-    fn inspect_err<F>(self, f: F) -> impl Outcome<Output = T, Error = E>
+    fn inspect_err<F>(self, f: F) -> impl_outcome!(T, E)
     where
         F: FnOnce(&Self::Error),
     {
@@ -40,7 +40,7 @@ impl<T, E> Outcome for Result<T, E> {
     }
 
     #[cfg(not(doc))]
-    fn map_err<F, O>(self, op: O) -> impl Outcome<Output = Self::Output, Error = F>
+    fn map_err<F, O>(self, op: O) -> impl_outcome!(Self::Output, F)
     where
         O: FnOnce(E) -> F,
     {
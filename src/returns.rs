@@ -0,0 +1,32 @@
+use crate::{imp::Marker, Outcome};
+
+pub trait _IexReturn<T, E> {
+    fn _iex_return(self, marker: Marker<E>) -> T;
+}
+
+impl<R: Outcome> _IexReturn<R::Output, R::Error> for R {
+    #[cfg_attr(any(debug_assertions, feature = "checked"), track_caller)]
+    fn _iex_return(self, marker: Marker<R::Error>) -> R::Output {
+        self.get_value_or_panic(marker)
+    }
+}
+
+pub trait FnOutput {
+    type Output;
+}
+
+impl<T> FnOutput for fn() -> T {
+    type Output = T;
+}
+
+// The never type, which cannot be named on stable otherwise
+type Never = <fn() -> ! as FnOutput>::Output;
+
+// Autoref specialization for diverging expressions, e.g. calls to `-> !` functions and macros that
+// expand to `return`. Their type is `!`, which does not implement `Outcome`, and passing them to a
+// generic function would make the type fall back to `()`.
+impl<T, E> _IexReturn<T, E> for &Never {
+    fn _iex_return(self, _marker: Marker<E>) -> T {
+        match *self {}
+    }
+}
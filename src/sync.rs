@@ -0,0 +1,415 @@
+//! Locks that are not poisoned by error propagation.
+//!
+//! Errors of [`#[iex]`](macro@crate::iex) functions are propagated by unwinding, so to
+//! [`std::sync::Mutex`] and [`std::sync::RwLock`], propagating an error through a scope holding a
+//! guard looks like a panic, and the lock gets poisoned. This is usually not what you want, because
+//! returning an error is normal control flow, and the protected data is expected to be in a
+//! consistent state.
+//!
+//! The locks in this module mirror the API of the [`std::sync`] ones, but are only poisoned by
+//! genuine panics. They are not interchangeable with them, though: the guards do not expose the
+//! underlying [`std::sync`] guards, so they cannot be used with [`std::sync::Condvar`].
+//!
+//! ```
+//! use iex::{iex, sync::Mutex, Outcome};
+//!
+//! #[iex]
+//! fn push_positive(values: &Mutex<Vec<i32>>, value: i32) -> Result<(), String> {
+//!     let mut values = values.lock().unwrap();
+//!     if value <= 0 {
+//!         return Err(format!("{value} is not positive"));
+//!     }
+//!     values.push(value);
+//!     Ok(())
+//! }
+//!
+//! let values = Mutex::new(Vec::new());
+//! assert_eq!(push_positive(&values, 1).into_result(), Ok(()));
+//! assert!(push_positive(&values, -1).into_result().is_err());
+//! assert!(!values.is_poisoned());
+//! assert_eq!(*values.lock().unwrap(), [1]);
+//! ```
+
+use crate::exception::Scope;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LockResult, PoisonError, TryLockError, TryLockResult};
+
+// The poison flag of a lock. The poison flag of the underlying std lock is ignored, because it's
+// also set on error propagation.
+struct Flag(AtomicBool);
+
+impl Flag {
+    const fn new() -> Self {
+        Self(AtomicBool::new(false))
+    }
+
+    fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn clear(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    // Must be called before the underlying guard is dropped. Locking and unlocking stay cheap, as
+    // the scope only accesses the thread-local if the lock is taken or released during unwinding.
+    fn done(&self, scope: &Scope) {
+        // Same as std, except that error propagation is not considered a panic
+        if scope.is_panicking() {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn result<G>(&self, guard: G) -> LockResult<G> {
+        if self.get() {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    fn try_result<G, H>(
+        &self,
+        result: TryLockResult<H>,
+        wrap: impl FnOnce(H) -> G,
+    ) -> TryLockResult<G> {
+        let guard = match result {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(TryLockError::WouldBlock),
+        };
+        Ok(self.result(wrap(guard))?)
+    }
+}
+
+/// A mutual exclusion primitive that is not poisoned by error propagation.
+///
+/// This is a wrapper around [`std::sync::Mutex`] with the same API. See [module-level
+/// documentation](self) for more information.
+pub struct Mutex<T: ?Sized> {
+    poison: Flag,
+    inner: std::sync::Mutex<T>,
+}
+
+/// An RAII guard of a [`Mutex`].
+pub struct MutexGuard<'a, T: ?Sized + 'a> {
+    poison: &'a Flag,
+    scope: Scope,
+    inner: std::sync::MutexGuard<'a, T>,
+}
+
+impl<T> Mutex<T> {
+    /// Create a new mutex in an unlocked state.
+    pub const fn new(value: T) -> Self {
+        Self {
+            poison: Flag::new(),
+            inner: std::sync::Mutex::new(value),
+        }
+    }
+
+    /// Consume the mutex, returning the underlying data.
+    ///
+    /// See [`std::sync::Mutex::into_inner`].
+    pub fn into_inner(self) -> LockResult<T> {
+        let value = self
+            .inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.poison.result(value)
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    fn wrap<'a>(&'a self, inner: std::sync::MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        MutexGuard {
+            poison: &self.poison,
+            scope: Scope::enter(),
+            inner,
+        }
+    }
+
+    /// Acquire the mutex, blocking the current thread until it is able to do so.
+    ///
+    /// See [`std::sync::Mutex::lock`].
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        let inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        self.poison.result(self.wrap(inner))
+    }
+
+    /// Attempt to acquire the mutex without blocking.
+    ///
+    /// See [`std::sync::Mutex::try_lock`].
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        self.poison
+            .try_result(self.inner.try_lock(), |inner| self.wrap(inner))
+    }
+
+    /// Determine whether the mutex is poisoned.
+    ///
+    /// Unlike [`std::sync::Mutex::is_poisoned`], this only returns `true` if a thread panicked
+    /// while holding the mutex, not if an error was propagated.
+    pub fn is_poisoned(&self) -> bool {
+        self.poison.get()
+    }
+
+    /// Clear the poisoned state from the mutex.
+    ///
+    /// See [`std::sync::Mutex::clear_poison`].
+    pub fn clear_poison(&self) {
+        self.poison.clear();
+    }
+
+    /// Return a mutable reference to the underlying data.
+    ///
+    /// See [`std::sync::Mutex::get_mut`].
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let value = self.inner.get_mut().unwrap_or_else(PoisonError::into_inner);
+        self.poison.result(value)
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+        match self.try_lock() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(TryLockError::Poisoned(err)) => d.field("data", &&**err.get_ref()),
+            Err(TryLockError::WouldBlock) => d.field("data", &format_args!("<locked>")),
+        };
+        d.field("poisoned", &self.is_poisoned());
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.poison.done(&self.scope);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+/// A reader-writer lock that is not poisoned by error propagation.
+///
+/// This is a wrapper around [`std::sync::RwLock`] with the same API. See [module-level
+/// documentation](self) for more information.
+pub struct RwLock<T: ?Sized> {
+    poison: Flag,
+    inner: std::sync::RwLock<T>,
+}
+
+/// An RAII guard for shared read access to an [`RwLock`].
+pub struct RwLockReadGuard<'a, T: ?Sized + 'a> {
+    inner: std::sync::RwLockReadGuard<'a, T>,
+}
+
+/// An RAII guard for exclusive write access to an [`RwLock`].
+pub struct RwLockWriteGuard<'a, T: ?Sized + 'a> {
+    poison: &'a Flag,
+    scope: Scope,
+    inner: std::sync::RwLockWriteGuard<'a, T>,
+}
+
+impl<T> RwLock<T> {
+    /// Create a new reader-writer lock in an unlocked state.
+    pub const fn new(value: T) -> Self {
+        Self {
+            poison: Flag::new(),
+            inner: std::sync::RwLock::new(value),
+        }
+    }
+
+    /// Consume the lock, returning the underlying data.
+    ///
+    /// See [`std::sync::RwLock::into_inner`].
+    pub fn into_inner(self) -> LockResult<T> {
+        let value = self
+            .inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.poison.result(value)
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    fn wrap_write<'a>(
+        &'a self,
+        inner: std::sync::RwLockWriteGuard<'a, T>,
+    ) -> RwLockWriteGuard<'a, T> {
+        RwLockWriteGuard {
+            poison: &self.poison,
+            scope: Scope::enter(),
+            inner,
+        }
+    }
+
+    /// Lock with shared read access, blocking the current thread until it can be acquired.
+    ///
+    /// See [`std::sync::RwLock::read`].
+    pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        self.poison.result(RwLockReadGuard { inner })
+    }
+
+    /// Attempt to acquire the lock with shared read access without blocking.
+    ///
+    /// See [`std::sync::RwLock::try_read`].
+    pub fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
+        self.poison
+            .try_result(self.inner.try_read(), |inner| RwLockReadGuard { inner })
+    }
+
+    /// Lock with exclusive write access, blocking the current thread until it can be acquired.
+    ///
+    /// See [`std::sync::RwLock::write`].
+    pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+        let inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        self.poison.result(self.wrap_write(inner))
+    }
+
+    /// Attempt to acquire the lock with exclusive write access without blocking.
+    ///
+    /// See [`std::sync::RwLock::try_write`].
+    pub fn try_write(&self) -> TryLockResult<RwLockWriteGuard<'_, T>> {
+        self.poison
+            .try_result(self.inner.try_write(), |inner| self.wrap_write(inner))
+    }
+
+    /// Determine whether the lock is poisoned.
+    ///
+    /// Unlike [`std::sync::RwLock::is_poisoned`], this only returns `true` if a thread panicked
+    /// while holding an exclusive lock, not if an error was propagated.
+    pub fn is_poisoned(&self) -> bool {
+        self.poison.get()
+    }
+
+    /// Clear the poisoned state from the lock.
+    ///
+    /// See [`std::sync::RwLock::clear_poison`].
+    pub fn clear_poison(&self) {
+        self.poison.clear();
+    }
+
+    /// Return a mutable reference to the underlying data.
+    ///
+    /// See [`std::sync::RwLock::get_mut`].
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let value = self.inner.get_mut().unwrap_or_else(PoisonError::into_inner);
+        self.poison.result(value)
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(TryLockError::Poisoned(err)) => d.field("data", &&**err.get_ref()),
+            Err(TryLockError::WouldBlock) => d.field("data", &format_args!("<locked>")),
+        };
+        d.field("poisoned", &self.is_poisoned());
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.poison.done(&self.scope);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
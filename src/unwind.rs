@@ -0,0 +1,194 @@
+use crate::exception;
+use std::fmt;
+use std::panic::{Location, UnwindSafe};
+use std::sync::{PoisonError, RwLock};
+
+/// The panic payload used to propagate errors.
+///
+/// Errors of [`#[iex]`](macro@crate::iex) functions are propagated by unwinding with this payload,
+/// while the error itself is kept in thread-local storage. Code that catches panics, e.g. test
+/// harnesses or thread pools, may observe it. Such code should use [`catch_unwind`] instead of
+/// [`std::panic::catch_unwind`] where possible, as the error is lost otherwise.
+///
+/// The payload is empty, so that throwing does not allocate, but the error can still be described
+/// while it is stored:
+///
+/// ```
+/// use iex::{iex, IexPanic, Outcome};
+///
+/// #[iex]
+/// fn fails() -> Result<(), i32> {
+///     Err(123)
+/// }
+///
+/// // A plugin host that is unaware of iex
+/// fn run_plugin(plugin: impl FnOnce() + std::panic::UnwindSafe) -> Result<(), String> {
+///     std::panic::catch_unwind(plugin).map_err(|payload| {
+///         match payload.downcast_ref::<IexPanic>().and_then(IexPanic::info) {
+///             Some(info) => info.to_string(),
+///             None => "plugin panicked".to_string(),
+///         }
+///     })
+/// }
+///
+/// let result = iex::raw::outcome(|marker| {
+///     run_plugin(|| unsafe { iex::raw::forward(fails(), marker) })
+/// });
+/// let message = result.into_result().unwrap().unwrap_err();
+/// assert!(message.starts_with("iex error of type `i32`"));
+/// ```
+#[derive(Debug)]
+pub struct IexPanic {
+    _private: (),
+}
+
+impl IexPanic {
+    pub(crate) const fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Describe the propagated error.
+    ///
+    /// The description is read from thread-local storage, so it is only available on the thread
+    /// the error was thrown on. Returns `None` otherwise.
+    pub fn info(&self) -> Option<ErrorInfo> {
+        exception::in_flight_info()
+    }
+}
+
+// iex forgets the payload when it catches the error, so dropping it means that the panic was
+// swallowed, and the error is no longer in flight.
+impl Drop for IexPanic {
+    fn drop(&mut self) {
+        exception::swallow();
+    }
+}
+
+/// A description of an error of an [`#[iex]`](macro@crate::iex) function.
+///
+/// Obtained from [`IexPanic::info`], and passed to the handler registered with
+/// [`set_uncaught_handler`].
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorInfo {
+    type_name: &'static str,
+    location: Option<&'static Location<'static>>,
+}
+
+impl ErrorInfo {
+    pub(crate) fn new(
+        type_name: &'static str,
+        location: Option<&'static Location<'static>>,
+    ) -> Self {
+        Self {
+            type_name,
+            location,
+        }
+    }
+
+    /// Get the name of the type of the error.
+    ///
+    /// The name is obtained with [`std::any::type_name`] and is only suitable for diagnostics.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Get the location the error was thrown at.
+    ///
+    /// The location is only recorded in debug builds and with the `checked` feature enabled.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.location
+    }
+}
+
+impl fmt::Display for ErrorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "iex error of type `{}`", self.type_name)?;
+        if let Some(location) = self.location {
+            write!(f, " thrown at {location}")?;
+        }
+        Ok(())
+    }
+}
+
+type Handler = Box<dyn Fn(&ErrorInfo) + Sync + Send + 'static>;
+
+static HANDLER: RwLock<Option<Handler>> = RwLock::new(None);
+
+/// Register a handler for errors that were never caught.
+///
+/// An error is lost if its unwinding is swallowed by [`std::panic::catch_unwind`], or if it leaves
+/// the thread, e.g. because a thread pool resumes the panic on a different thread. iex notices this
+/// when the thread throws the next error or exits, and calls the handler with a description of
+/// the lost error. In checked builds, the former situation panics instead.
+///
+/// The default handler prints a message to standard error. The handler may be called during
+/// thread exit, so it must not call [`#[iex]`](macro@crate::iex) functions.
+///
+/// # Example
+///
+/// ```
+/// iex::set_uncaught_handler(Box::new(|info| {
+///     eprintln!("lost {info}");
+/// }));
+/// # drop(iex::take_uncaught_handler());
+/// ```
+pub fn set_uncaught_handler(handler: Handler) {
+    *HANDLER.write().unwrap_or_else(PoisonError::into_inner) = Some(handler);
+}
+
+/// Unregister the handler for errors that were never caught, returning it.
+///
+/// The default handler is used afterwards. Returns `None` if no handler was registered.
+pub fn take_uncaught_handler() -> Option<Handler> {
+    HANDLER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+}
+
+#[cold]
+pub(crate) fn report_uncaught(info: &ErrorInfo) {
+    match &*HANDLER.read().unwrap_or_else(PoisonError::into_inner) {
+        Some(handler) => handler(info),
+        None => eprintln!("{info} was never caught"),
+    }
+}
+
+/// Invoke a closure, capturing the cause of an unwinding panic if one occurs.
+///
+/// This is [`std::panic::catch_unwind`], except that errors of [`#[iex]`](macro@crate::iex)
+/// functions pass through it, so that they reach the code that handles them. Use this function in
+/// code that may be called while an error is propagated, e.g. in callbacks invoked by
+/// [`#[iex]`](macro@crate::iex) functions.
+///
+/// # Example
+///
+/// ```
+/// use iex::{iex, Outcome};
+///
+/// fn run_plugin(plugin: impl FnOnce() + std::panic::UnwindSafe) -> bool {
+///     iex::catch_unwind(plugin).is_ok()
+/// }
+///
+/// #[iex]
+/// fn host(fail: bool) -> Result<bool, String> {
+///     let mut ok = true;
+///     iex::raw::outcome(|marker| {
+///         ok = run_plugin(|| unsafe {
+///             iex::raw::forward(if fail { Err("error".to_string()) } else { Ok(()) }, marker)
+///         });
+///     })?;
+///     Ok(ok && !run_plugin(|| panic!("bug")))
+/// }
+///
+/// assert_eq!(host(false).into_result(), Ok(true));
+/// assert_eq!(host(true).into_result(), Err("error".to_string()));
+/// ```
+pub fn catch_unwind<F: FnOnce() -> R + UnwindSafe, R>(f: F) -> std::thread::Result<R> {
+    std::panic::catch_unwind(f).map_err(|payload| {
+        if payload.is::<IexPanic>() {
+            std::panic::resume_unwind(payload);
+        }
+        payload
+    })
+}
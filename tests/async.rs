@@ -0,0 +1,152 @@
+use iex::{iex, Outcome};
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: std::pin::Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+#[iex]
+fn checked_divide(a: u32, b: u32) -> Result<u32, &'static str> {
+    a.checked_div(b).ok_or("Cannot divide by zero")
+}
+
+#[iex]
+async fn async_divide(a: u32, b: u32) -> Result<u32, &'static str> {
+    YieldNow(false).await;
+    Ok(checked_divide(a, b)?)
+}
+
+#[iex]
+async fn async_sum(s: &str, b: u32) -> Result<u32, String> {
+    let mut sum = 0;
+    for a in s.split(',') {
+        let a: u32 = a.parse().map_err(|_| format!("Invalid number {a}"))?;
+        sum += async_divide(a, b).await?;
+    }
+    Ok(sum)
+}
+
+#[test]
+fn async_fn() {
+    assert_eq!(block_on(async_divide(6, 2)).into_result(), Ok(3));
+    assert_eq!(
+        block_on(async_divide(6, 0)).into_result(),
+        Err("Cannot divide by zero"),
+    );
+    assert_eq!(block_on(async_sum("2,4,6", 2)).into_result(), Ok(6));
+    assert_eq!(
+        block_on(async_sum("2,x", 2)).into_result(),
+        Err("Invalid number x".to_string()),
+    );
+    assert_eq!(
+        block_on(async_sum("2,4", 0)).into_result(),
+        Err("Cannot divide by zero".to_string()),
+    );
+}
+
+#[test]
+fn moves_between_threads() {
+    let mut future = Box::pin(async_sum("2,4", 0));
+    let mut cx = Context::from_waker(Waker::noop());
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    let result = std::thread::spawn(move || {
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output.into_result();
+            }
+        }
+    })
+    .join()
+    .unwrap();
+    assert_eq!(result, Err("Cannot divide by zero".to_string()));
+}
+
+struct Divider(u32);
+
+impl Divider {
+    #[iex]
+    async fn divide(&self, a: u32) -> Result<u32, &'static str> {
+        YieldNow(false).await;
+        checked_divide(a, self.0)
+    }
+}
+
+trait AsyncDivide {
+    #[iex]
+    async fn divide_by(&self, b: u32) -> Result<u32, &'static str>;
+}
+
+impl AsyncDivide for u32 {
+    #[iex]
+    async fn divide_by(&self, b: u32) -> Result<u32, &'static str> {
+        Divider(b).divide(*self).await
+    }
+}
+
+#[test]
+fn async_method() {
+    assert_eq!(block_on(Divider(2).divide(6)).into_result(), Ok(3));
+    assert_eq!(block_on(6.divide_by(3)).into_result(), Ok(2));
+    assert_eq!(
+        block_on(6.divide_by(0)).into_result(),
+        Err("Cannot divide by zero"),
+    );
+}
+
+#[iex]
+async fn sum_or_zero(s: &str) -> Result<u32, String> {
+    if s.is_empty() {
+        return Ok(0);
+    }
+    async_sum(s, 1).await
+}
+
+#[test]
+fn mixed_returns() {
+    assert_eq!(block_on(sum_or_zero("")).into_result(), Ok(0));
+    assert_eq!(block_on(sum_or_zero("1,2")).into_result(), Ok(3));
+}
+
+#[test]
+fn outcome_outlives_arguments() {
+    let result = block_on(async {
+        let s = String::from("2,4");
+        async_sum(&s, 2).await
+    });
+    assert_eq!(result.into_result(), Ok(3));
+}
+
+#[iex]
+async fn multiplier(s: &str) -> Result<impl Fn(u32) -> u32, String> {
+    let x = async_sum(s, 1).await?;
+    Ok(move |y| x * y)
+}
+
+#[test]
+fn impl_trait() {
+    let multiply = block_on(multiplier("1,2")).into_result().unwrap();
+    assert_eq!(multiply(2), 6);
+}
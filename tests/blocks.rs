@@ -0,0 +1,113 @@
+use iex::{iex, Outcome};
+
+struct Counter(u32);
+
+#[iex]
+impl Counter {
+    fn increment(&mut self) -> Result<u32, &'static str> {
+        self.0 = self.0.checked_add(1).ok_or("Overflow")?;
+        Ok(self.0)
+    }
+
+    fn get(&self) -> u32 {
+        self.0
+    }
+
+    #[iex(skip)]
+    fn reset(&mut self) -> Result<u32, &'static str> {
+        Ok(std::mem::take(&mut self.0))
+    }
+
+    const fn start(value: u32) -> Result<Self, &'static str> {
+        if value == u32::MAX {
+            Err("Overflow")
+        } else {
+            Ok(Counter(value))
+        }
+    }
+}
+
+const ZERO: Result<Counter, &str> = Counter::start(0);
+
+#[test]
+fn impl_block() {
+    let mut counter = Counter(u32::MAX - 1);
+    assert_eq!(counter.increment().into_result(), Ok(u32::MAX));
+    assert_eq!(counter.increment().into_result(), Err("Overflow"));
+    assert_eq!(counter.get(), u32::MAX);
+    // Not rewritten, so this is a plain Result
+    let result: Result<u32, &str> = counter.reset();
+    assert_eq!(result, Ok(u32::MAX));
+    // Const functions are skipped
+    assert_eq!(ZERO.map(|counter| counter.0), Ok(0));
+    assert!(Counter::start(u32::MAX).is_err());
+}
+
+#[iex]
+trait Parse: Sized {
+    fn parse(s: &str) -> Result<Self, String>;
+
+    fn parse_pair(s: &str) -> Result<(Self, Self), String> {
+        let (a, b) = s.split_once(',').ok_or("Expected a comma")?;
+        Ok((Self::parse(a)?, Self::parse(b)?))
+    }
+}
+
+#[iex]
+impl Parse for u8 {
+    fn parse(s: &str) -> Result<Self, String> {
+        s.parse().map_err(|_| format!("Invalid number {s:?}"))
+    }
+}
+
+#[test]
+fn trait_and_impl() {
+    assert_eq!(u8::parse_pair("1,2").into_result(), Ok((1, 2)));
+    assert_eq!(
+        u8::parse_pair("1;2").into_result(),
+        Err("Expected a comma".to_string())
+    );
+    assert_eq!(
+        u8::parse_pair("1,x").into_result(),
+        Err("Invalid number \"x\"".to_string())
+    );
+}
+
+#[iex]
+mod ops {
+    pub fn divide(a: u32, b: u32) -> std::result::Result<u32, &'static str> {
+        a.checked_div(b).ok_or("Cannot divide by zero")
+    }
+
+    pub const fn double(a: u32) -> Result<u32, &'static str> {
+        match a.checked_mul(2) {
+            Some(a) => Ok(a),
+            None => Err("Overflow"),
+        }
+    }
+
+    pub struct Halver;
+
+    impl Halver {
+        pub fn halve(&self, a: u32) -> Result<u32, &'static str> {
+            Ok(divide(a, 2)?)
+        }
+    }
+
+    pub mod nested {
+        pub fn quarter(a: u32) -> Result<u32, &'static str> {
+            super::Halver.halve(super::Halver.halve(a)?)
+        }
+    }
+}
+
+#[test]
+fn module() {
+    assert_eq!(
+        ops::divide(6, 0).into_result(),
+        Err("Cannot divide by zero")
+    );
+    assert_eq!(ops::Halver.halve(6).into_result(), Ok(3));
+    assert_eq!(ops::double(3), Ok(6));
+    assert_eq!(ops::nested::quarter(8).into_result(), Ok(2));
+}
@@ -0,0 +1,101 @@
+use iex::{iex, Outcome};
+use std::str::FromStr;
+
+#[iex]
+fn parse_coordinate(s: &str) -> Result<i32, String> {
+    s.trim()
+        .parse()
+        .map_err(|_| format!("Invalid coordinate: {s}"))
+}
+
+#[derive(Debug, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl FromStr for Point {
+    type Err = String;
+
+    #[iex(boundary)]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s.split_once(',').ok_or("Missing comma")?;
+        Ok(Point {
+            x: parse_coordinate(x)?,
+            y: parse_coordinate(y)?,
+        })
+    }
+}
+
+#[test]
+fn from_str() {
+    assert_eq!("1, 2".parse(), Ok(Point { x: 1, y: 2 }));
+    assert_eq!("1".parse::<Point>(), Err("Missing comma".to_string()));
+    assert_eq!(
+        "1, y".parse::<Point>(),
+        Err("Invalid coordinate:  y".to_string())
+    );
+}
+
+struct Positive(i32);
+
+#[iex(boundary)]
+impl TryFrom<&str> for Positive {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, String> {
+        let value = parse_coordinate(s)?;
+        if value <= 0 {
+            return Err(format!("Not positive: {value}"));
+        }
+        Ok(Positive(value))
+    }
+}
+
+#[test]
+fn impl_block() {
+    assert_eq!(Positive::try_from("5").map(|p| p.0), Ok(5));
+    assert_eq!(
+        Positive::try_from("-5").map(|p| p.0),
+        Err("Not positive: -5".to_string())
+    );
+}
+
+struct Coordinates<'a>(std::str::Split<'a, char>);
+
+#[iex]
+fn parse_even(s: &str) -> Option<i32> {
+    let value = parse_coordinate(s).ok()?;
+    (value % 2 == 0).then_some(value)
+}
+
+impl Iterator for Coordinates<'_> {
+    type Item = i32;
+
+    #[iex(boundary)]
+    fn next(&mut self) -> Option<i32> {
+        parse_even(self.0.next()?)
+    }
+}
+
+#[test]
+fn option() {
+    let coordinates = Coordinates("2,4,5,6".split(','));
+    assert_eq!(coordinates.collect::<Vec<_>>(), [2, 4]);
+}
+
+#[iex(boundary)]
+fn sum(values: &[&str]) -> Result<i32, String> {
+    let mut sum = 0;
+    for value in values {
+        sum += parse_coordinate(value)?;
+    }
+    Ok(sum)
+}
+
+#[test]
+fn plain_result() {
+    let result: Result<i32, String> = sum(&["1", "2"]);
+    assert_eq!(result, Ok(3));
+    assert_eq!(sum(&["1", "x"]), Err("Invalid coordinate: x".to_string()));
+}
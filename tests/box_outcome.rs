@@ -0,0 +1,117 @@
+use iex::{iex, BoxOutcome, Outcome, SendBoxOutcome};
+use std::cell::Cell;
+
+#[iex]
+fn checked_divide(a: u32, b: u32) -> Result<u32, &'static str> {
+    a.checked_div(b).ok_or("Cannot divide by zero")
+}
+
+#[iex]
+fn checked_subtract(a: u32, b: u32) -> Result<u32, &'static str> {
+    a.checked_sub(b).ok_or("Overflow")
+}
+
+type Op = fn(u32, u32) -> BoxOutcome<'static, u32, &'static str>;
+
+const OPS: [Op; 2] = [
+    |a, b| BoxOutcome::new(checked_divide(a, b)),
+    |a, b| BoxOutcome::new(checked_subtract(a, b)),
+];
+
+#[iex]
+fn eval(ops: &[(usize, u32)]) -> Result<u32, &'static str> {
+    let mut acc = 100;
+    for &(op, b) in ops {
+        acc = OPS[op](acc, b)?;
+    }
+    Ok(acc)
+}
+
+#[test]
+fn dispatch_table() {
+    assert_eq!(eval(&[(0, 5), (1, 10)]).into_result(), Ok(10));
+    assert_eq!(
+        eval(&[(1, 100), (0, 0)]).into_result(),
+        Err("Cannot divide by zero")
+    );
+    assert_eq!(eval(&[(1, 101)]).into_result(), Err("Overflow"));
+}
+
+struct Pending<'a> {
+    outcomes: Vec<BoxOutcome<'a, u32, &'static str>>,
+}
+
+#[iex]
+fn sum(pending: Pending<'_>) -> Result<u32, String> {
+    let mut sum = 0;
+    for outcome in pending.outcomes {
+        sum += outcome.map_err(|e| format!("Failed: {e}"))?;
+    }
+    Ok(sum)
+}
+
+#[iex]
+fn sum_big(big: [u64; 8]) -> Result<u32, &'static str> {
+    checked_subtract(big.iter().sum::<u64>() as u32, 1)
+}
+
+#[test]
+fn stored() {
+    let big = [1u64; 8];
+    let pending = Pending {
+        outcomes: vec![
+            BoxOutcome::new(checked_divide(4, 2)),
+            BoxOutcome::new(Ok(3)),
+            // Too large to be stored inline
+            BoxOutcome::new(sum_big(big)),
+        ],
+    };
+    assert_eq!(sum(pending).into_result(), Ok(12));
+
+    let pending = Pending {
+        outcomes: vec![
+            BoxOutcome::new(checked_divide(4, 2)),
+            BoxOutcome::new(Err("Nope")),
+        ],
+    };
+    assert_eq!(sum(pending).into_result(), Err("Failed: Nope".to_string()));
+}
+
+struct DropCounter<'a>(&'a Cell<usize>);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[iex]
+fn consume(_counter: DropCounter<'_>, _big: [u64; 8]) -> Result<(), ()> {
+    Ok(())
+}
+
+#[test]
+fn drops_unused() {
+    let count = Cell::new(0);
+    let inline = BoxOutcome::new(Ok::<_, ()>(DropCounter(&count)));
+    let boxed = BoxOutcome::new(consume(DropCounter(&count), [0; 8]));
+    drop(inline);
+    drop(boxed);
+    assert_eq!(count.get(), 2);
+    BoxOutcome::new(consume(DropCounter(&count), [0; 8]))
+        .into_result()
+        .unwrap();
+    assert_eq!(count.get(), 3);
+}
+
+#[test]
+fn send() {
+    let outcome = SendBoxOutcome::new(checked_divide(6, 0));
+    let result = std::thread::spawn(move || outcome.into_result())
+        .join()
+        .unwrap();
+    assert_eq!(result, Err("Cannot divide by zero"));
+
+    let outcome: BoxOutcome<'_, u32, &'static str> = SendBoxOutcome::new(Ok(1)).into();
+    assert_eq!(outcome.into_result(), Ok(1));
+}
@@ -0,0 +1,36 @@
+#![cfg(any(debug_assertions, feature = "checked"))]
+
+use iex::{iex, Outcome};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[iex]
+fn fails() -> Result<(), i32> {
+    Err(123)
+}
+
+#[iex]
+fn propagates() -> Result<(), i32> {
+    fails()?;
+    Ok(())
+}
+
+#[test]
+fn unconsumed() {
+    // Swallowing the unwind with a foreign catch_unwind leaves the error on the stack
+    let swallows = iex::raw::outcome(|marker| {
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+            iex::raw::forward(propagates(), marker)
+        }));
+    });
+    assert_eq!(swallows.into_result(), Ok::<(), i32>(()));
+
+    let message = catch_unwind(|| fails().into_result())
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+    assert!(message.contains("was never caught"), "{message}");
+    assert!(message.contains("tests/checked.rs"), "{message}");
+
+    // The stack is usable again afterwards
+    assert_eq!(propagates().into_result(), Err(123));
+}
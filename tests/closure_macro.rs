@@ -0,0 +1,87 @@
+use iex::{iex, Outcome};
+
+#[iex]
+fn parse_digit(c: u8) -> Result<u8, String> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        _ => Err(format!("Not a digit: {}", c as char)),
+    }
+}
+
+#[iex]
+fn sum_digits(words: &[&str]) -> Result<u32, String> {
+    let digit_sum = iex::closure!(|word: &str| -> Result<u32, String> {
+        let mut sum = 0;
+        for &c in word.as_bytes() {
+            sum += parse_digit(c)? as u32;
+        }
+        Ok(sum)
+    });
+    let mut total = 0;
+    for &word in words {
+        total += digit_sum.call((word,))?;
+    }
+    Ok(total)
+}
+
+#[test]
+fn borrowed_args() {
+    assert_eq!(sum_digits(&["12", "345"]).into_result(), Ok(15));
+    assert_eq!(
+        sum_digits(&["12", "3x5"]).into_result(),
+        Err("Not a digit: x".to_string())
+    );
+}
+
+#[test]
+fn fn_mut() {
+    let mut seen = Vec::new();
+    let mut record = iex::closure!(|bytes: &[u8], index: usize| -> Result<(), String> {
+        seen.push(parse_digit(bytes[index])?);
+        Ok(())
+    });
+    assert_eq!(record.call_mut((b"123", 1)).into_result(), Ok(()));
+    assert_eq!(
+        record.call_mut((b"1x3", 1)).into_result(),
+        Err("Not a digit: x".to_string())
+    );
+    assert_eq!(record.call_mut((b"123", 2)).into_result(), Ok(()));
+    assert_eq!(seen, [2, 3]);
+}
+
+#[test]
+fn fn_once() {
+    let prefix = String::from("digit ");
+    let describe = iex::closure!(move |c: &u8| -> Result<String, String> {
+        let mut prefix = prefix;
+        prefix.push((b'0' + parse_digit(*c)?) as char);
+        Ok(prefix)
+    });
+    assert_eq!(
+        describe.call_once((&b'7',)).into_result(),
+        Ok("digit 7".to_string())
+    );
+}
+
+#[test]
+fn early_return() {
+    let first_digit = iex::closure!(|s: &str| {
+        for &c in s.as_bytes() {
+            if c.is_ascii_digit() {
+                return Ok(parse_digit(c)?);
+            }
+        }
+        Err("No digits".to_string())
+    });
+    assert_eq!(first_digit.call(("ab4c5",)).into_result(), Ok(4));
+    assert_eq!(
+        first_digit.call(("abc",)).into_result(),
+        Err("No digits".to_string())
+    );
+}
+
+#[test]
+fn no_args() {
+    let zero = iex::closure!(|| -> Result<u8, String> { parse_digit(b'0') });
+    assert_eq!(zero.call(()).into_result(), Ok(0));
+}
@@ -22,3 +22,37 @@ fn example() -> Result<u32, &'static str> {
 fn closure() {
     assert_eq!(example().into_result(), Ok(123));
 }
+
+#[iex]
+async fn async_divide(a: u32, b: u32) -> Result<u32, &'static str> {
+    example().into_result()?;
+    if b == 0 {
+        Err("Cannot divide by zero")
+    } else {
+        Ok(a / b)
+    }
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn async_closure() {
+    let offset = 1;
+    let closure = #[iex]
+    async move |a: u32, b: u32| -> Result<u32, &'static str> {
+        Ok(async_divide(a, b).await? + offset)
+    };
+    assert_eq!(block_on(closure(6, 2)).into_result(), Ok(4));
+    assert_eq!(
+        block_on(closure(6, 0)).into_result(),
+        Err("Cannot divide by zero"),
+    );
+}
@@ -0,0 +1,138 @@
+use iex::{iex, Outcome};
+use std::cell::Cell;
+
+#[iex]
+fn parse(s: &str) -> Result<i32, String> {
+    s.parse().map_err(|_| format!("Invalid number: {s}"))
+}
+
+#[iex]
+fn halve(x: i32) -> Result<i32, String> {
+    if x % 2 == 0 {
+        Ok(x / 2)
+    } else {
+        Err(format!("Odd number: {x}"))
+    }
+}
+
+#[iex]
+fn parse_and_halve(s: &str) -> Result<i32, String> {
+    parse(s).and_then(halve)
+}
+
+#[test]
+fn map() {
+    assert_eq!(parse("12").map(|x| x + 1).into_result(), Ok(13));
+    assert_eq!(
+        parse("x").map(|x| x + 1).into_result(),
+        Err("Invalid number: x".to_string())
+    );
+    assert_eq!(
+        Ok::<i32, ()>(1).map(|x| x.to_string()).into_result(),
+        Ok("1".to_string())
+    );
+}
+
+#[test]
+fn and_then() {
+    assert_eq!(parse_and_halve("12").into_result(), Ok(6));
+    assert_eq!(
+        parse_and_halve("13").into_result(),
+        Err("Odd number: 13".to_string())
+    );
+    assert_eq!(
+        parse_and_halve("x").into_result(),
+        Err("Invalid number: x".to_string())
+    );
+    assert_eq!(
+        parse("12").and_then(|x| Ok(x * 2)).into_result(),
+        Ok::<_, String>(24)
+    );
+}
+
+#[test]
+fn inspect() {
+    let seen = Cell::new(None);
+    assert_eq!(
+        parse("12").inspect(|x| seen.set(Some(*x))).into_result(),
+        Ok(12)
+    );
+    assert_eq!(seen.get(), Some(12));
+
+    seen.set(None);
+    assert!(parse("x")
+        .inspect(|x| seen.set(Some(*x)))
+        .into_result()
+        .is_err());
+    assert_eq!(seen.get(), None);
+}
+
+#[test]
+fn lazy() {
+    let called = Cell::new(false);
+    let outcome = parse("12").map(|x| {
+        called.set(true);
+        x
+    });
+    assert!(!called.get());
+    assert_eq!(outcome.into_result(), Ok(12));
+    assert!(called.get());
+}
+
+#[test]
+fn ok_err() {
+    assert_eq!(parse("12").ok(), Some(12));
+    assert_eq!(parse("x").ok(), None);
+    assert_eq!(parse("12").err(), None);
+    assert_eq!(parse("x").err(), Some("Invalid number: x".to_string()));
+}
+
+#[iex]
+fn parse_or_zero(s: &str) -> Result<i32, String> {
+    parse(s).or_else(|_| Ok(0))
+}
+
+#[iex]
+fn parse_either(a: &str, b: &str) -> Result<i32, String> {
+    parse(a).or_else(|_| parse(b))
+}
+
+#[test]
+fn or_else() {
+    assert_eq!(parse_or_zero("12").into_result(), Ok(12));
+    assert_eq!(parse_or_zero("x").into_result(), Ok(0));
+    assert_eq!(parse_either("1", "x").into_result(), Ok(1));
+    assert_eq!(parse_either("x", "2").into_result(), Ok(2));
+    assert_eq!(
+        parse_either("x", "y").into_result(),
+        Err("Invalid number: y".to_string())
+    );
+    assert_eq!(
+        parse("x")
+            .or_else(|err| Err::<i32, _>(err.len()))
+            .into_result(),
+        Err(17)
+    );
+}
+
+#[test]
+fn unwrap_or() {
+    assert_eq!(parse("12").unwrap_or(0), 12);
+    assert_eq!(parse("x").unwrap_or(0), 0);
+    assert_eq!(parse("x").unwrap_or_else(|err| err.len() as i32), 17);
+    assert_eq!(parse("12").unwrap_or_default(), 12);
+    assert_eq!(parse("x").unwrap_or_default(), 0);
+}
+
+#[iex]
+fn panics() -> Result<i32, String> {
+    panic!("Foreign panic")
+}
+
+#[test]
+fn foreign_panics() {
+    assert!(std::panic::catch_unwind(|| panics().unwrap_or(0)).is_err());
+    assert!(
+        std::panic::catch_unwind(|| panics().or_else(|_| Ok::<_, ()>(0)).into_result()).is_err()
+    );
+}
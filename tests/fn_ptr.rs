@@ -0,0 +1,107 @@
+use iex::{iex, FnOutcome, Outcome};
+
+#[derive(Debug, PartialEq)]
+enum Trap {
+    StackUnderflow,
+    DivisionByZero,
+}
+
+struct Vm {
+    stack: Vec<i32>,
+}
+
+impl Vm {
+    #[iex(fn_ptr)]
+    fn pop(&mut self) -> Result<i32, Trap> {
+        self.stack.pop().ok_or(Trap::StackUnderflow)
+    }
+}
+
+#[iex(fn_ptr)]
+fn push_one(vm: &mut Vm) -> Result<(), Trap> {
+    vm.stack.push(1);
+    Ok(())
+}
+
+#[iex(fn_ptr)]
+fn add(vm: &mut Vm) -> Result<(), Trap> {
+    let a = vm.pop()?;
+    let b = vm.pop()?;
+    vm.stack.push(a + b);
+    Ok(())
+}
+
+#[iex(fn_ptr)]
+#[inline(never)]
+fn div(vm: &mut Vm) -> Result<(), Trap> {
+    let a = vm.pop()?;
+    let b = vm.pop()?;
+    vm.stack.push(b.checked_div(a).ok_or(Trap::DivisionByZero)?);
+    Ok(())
+}
+
+type Handler = fn(&mut Vm) -> FnOutcome<(&mut Vm,), (), Trap>;
+
+const HANDLERS: [Handler; 3] = [push_one, add, div];
+
+#[iex]
+fn run(vm: &mut Vm, code: &[u8]) -> Result<(), Trap> {
+    for &opcode in code {
+        HANDLERS[opcode as usize](vm)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn dispatch_table() {
+    let mut vm = Vm { stack: Vec::new() };
+    assert_eq!(run(&mut vm, &[0, 0, 1, 0, 0, 1, 2]).into_result(), Ok(()));
+    assert_eq!(vm.stack, [1]);
+    assert_eq!(run(&mut vm, &[1]).into_result(), Err(Trap::StackUnderflow));
+    vm.stack = vec![1, 0];
+    assert_eq!(run(&mut vm, &[2]).into_result(), Err(Trap::DivisionByZero));
+}
+
+#[iex(fn_ptr)]
+fn first_word(s: &str) -> Result<&str, String> {
+    s.split_whitespace()
+        .next()
+        .ok_or_else(|| format!("No words in {s:?}"))
+}
+
+// Only the argument types must spell out their lifetimes, the body may elide them
+#[iex(fn_ptr)]
+fn first_char(s: &str) -> Result<char, String> {
+    let mut chars: std::str::Chars = s.chars();
+    chars.next().ok_or_else(|| "Empty string".to_string())
+}
+
+#[iex(fn_ptr)]
+fn parse<T: std::str::FromStr>((s, radix): (&str, u32)) -> Result<T, String> {
+    let _ = radix;
+    s.parse().map_err(|_| format!("Invalid number {s:?}"))
+}
+
+#[test]
+fn signatures() {
+    let f: fn(&str) -> FnOutcome<(&str,), &str, String> = first_word;
+    assert_eq!(f("hello world").into_result(), Ok("hello"));
+    assert_eq!(f(" ").into_result(), Err("No words in \" \"".to_string()));
+
+    let f: fn(&str) -> FnOutcome<(&str,), char, String> = first_char;
+    assert_eq!(f("hello").into_result(), Ok('h'));
+    assert_eq!(f("").into_result(), Err("Empty string".to_string()));
+
+    type Parse = fn((&str, u32)) -> FnOutcome<((&str, u32),), u8, String>;
+    let f: Parse = parse::<u8>;
+    assert_eq!(f(("12", 10)).into_result(), Ok(12));
+    assert_eq!(
+        f(("256", 10)).into_result(),
+        Err("Invalid number \"256\"".to_string())
+    );
+
+    let f: fn(&mut Vm) -> FnOutcome<(&mut Vm,), i32, Trap> = Vm::pop;
+    let mut vm = Vm { stack: vec![1] };
+    assert_eq!(f(&mut vm).into_result(), Ok(1));
+    assert_eq!(f(&mut vm).into_result(), Err(Trap::StackUnderflow));
+}
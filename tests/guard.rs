@@ -0,0 +1,126 @@
+use iex::{iex, Outcome};
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[iex]
+fn fails() -> Result<(), String> {
+    Err("error".to_string())
+}
+
+#[iex]
+fn guarded(fail: bool, errors: &Cell<u32>, successes: &Cell<u32>) -> Result<(), String> {
+    let _on_error = iex::on_error(|| errors.set(errors.get() + 1));
+    let _on_success = iex::on_success(|| successes.set(successes.get() + 1));
+    if fail {
+        fails()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn matching_path() {
+    let errors = Cell::new(0);
+    let successes = Cell::new(0);
+    guarded(false, &errors, &successes).into_result().unwrap();
+    assert_eq!((errors.get(), successes.get()), (0, 1));
+    guarded(true, &errors, &successes)
+        .into_result()
+        .unwrap_err();
+    assert_eq!((errors.get(), successes.get()), (1, 1));
+}
+
+#[test]
+fn foreign_panic() {
+    let errors = Cell::new(0);
+    let successes = Cell::new(0);
+    let propagating = Cell::new(None);
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let _on_error = iex::on_error(|| errors.set(errors.get() + 1));
+        let _on_success = iex::on_success(|| successes.set(successes.get() + 1));
+        struct Check<'a>(&'a Cell<Option<bool>>);
+        impl Drop for Check<'_> {
+            fn drop(&mut self) {
+                self.0.set(Some(iex::is_propagating()));
+            }
+        }
+        let _check = Check(&propagating);
+        panic!("foreign");
+    }));
+    assert_eq!((errors.get(), successes.get()), (0, 0));
+    assert_eq!(propagating.get(), Some(false));
+}
+
+#[test]
+fn panic_after_swallowed_error() {
+    // The swallowed error is no longer propagated, so the panic is a genuine one
+    let _ = iex::raw::outcome(|marker| {
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+            iex::raw::forward(fails(), marker)
+        }));
+    })
+    .into_result();
+    let errors = Cell::new(0);
+    let successes = Cell::new(0);
+    let propagating = Cell::new(None);
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let _on_error = iex::on_error(|| errors.set(errors.get() + 1));
+        let _on_success = iex::on_success(|| successes.set(successes.get() + 1));
+        struct Check<'a>(&'a Cell<Option<bool>>);
+        impl Drop for Check<'_> {
+            fn drop(&mut self) {
+                self.0.set(Some(iex::is_propagating()));
+            }
+        }
+        let _check = Check(&propagating);
+        panic!("foreign");
+    }));
+    assert_eq!((errors.get(), successes.get()), (0, 0));
+    assert_eq!(propagating.get(), Some(false));
+}
+
+#[test]
+fn not_propagating() {
+    assert!(!iex::is_propagating());
+    let _ = fails().into_result();
+    assert!(!iex::is_propagating());
+}
+
+#[test]
+fn caught_inside_scope() {
+    // An error that is thrown and caught within the scope doesn't count
+    let errors = Cell::new(0);
+    let successes = Cell::new(0);
+    {
+        let _on_error = iex::on_error(|| errors.set(errors.get() + 1));
+        let _on_success = iex::on_success(|| successes.set(successes.get() + 1));
+        let _ = fails().into_result();
+    }
+    assert_eq!((errors.get(), successes.get()), (0, 1));
+}
+
+#[test]
+fn during_propagation() {
+    // Guards created in destructors that run during propagation see their own scope only
+    struct Nested<'a>(&'a Cell<u32>, &'a Cell<u32>);
+
+    impl Drop for Nested<'_> {
+        fn drop(&mut self) {
+            assert!(iex::is_propagating());
+            let _ = guarded(false, self.0, self.1).into_result();
+            let _ = guarded(true, self.0, self.1).into_result();
+        }
+    }
+
+    #[iex]
+    fn fails_with_nested(errors: &Cell<u32>, successes: &Cell<u32>) -> Result<(), String> {
+        let _nested = Nested(errors, successes);
+        fails()
+    }
+
+    let errors = Cell::new(0);
+    let successes = Cell::new(0);
+    fails_with_nested(&errors, &successes)
+        .into_result()
+        .unwrap_err();
+    assert_eq!((errors.get(), successes.get()), (1, 1));
+}
@@ -0,0 +1,96 @@
+use iex::{iex, IexFn, IexFnMut, IexFnOnce, Outcome};
+
+#[iex]
+fn apply_all<E>(values: &[i32], f: &impl IexFn<(i32,), E, Output = i32>) -> Result<Vec<i32>, E> {
+    let mut result = Vec::new();
+    for &value in values {
+        result.push(f.iex_call((value,))?);
+    }
+    Ok(result)
+}
+
+#[iex]
+fn halve(x: i32) -> Result<i32, String> {
+    if x % 2 == 0 {
+        Ok(x / 2)
+    } else {
+        Err(format!("Odd number: {x}"))
+    }
+}
+
+#[test]
+fn functions() {
+    assert_eq!(apply_all(&[2, 4], &halve).into_result(), Ok(vec![1, 2]));
+    assert_eq!(
+        apply_all(&[2, 3], &halve).into_result(),
+        Err("Odd number: 3".to_string())
+    );
+}
+
+#[test]
+fn closures() {
+    let checked_neg = |x: i32| x.checked_neg().ok_or("Overflow");
+    assert_eq!(
+        apply_all(&[1, 2], &checked_neg).into_result(),
+        Ok(vec![-1, -2])
+    );
+    assert_eq!(
+        apply_all(&[1, i32::MIN], &checked_neg).into_result(),
+        Err("Overflow")
+    );
+
+    let offset = 10;
+    let add = iex::closure!(|x: i32| -> Result<i32, ()> { Ok(x + offset) });
+    assert_eq!(apply_all(&[1, 2], &add).into_result(), Ok(vec![11, 12]));
+}
+
+#[iex]
+fn count_until<E>(limit: i32, mut f: impl IexFnMut<(), E, Output = bool>) -> Result<i32, E> {
+    let mut count = 0;
+    while count < limit && f.iex_call_mut(())? {
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[test]
+fn fn_mut() {
+    let mut calls = 0;
+    let result = count_until(5, || {
+        calls += 1;
+        Ok::<_, ()>(calls < 3)
+    });
+    assert_eq!(result.into_result(), Ok(2));
+    assert_eq!(calls, 3);
+
+    let mut remaining = 1;
+    let result = count_until(
+        5,
+        iex::closure!(|| -> Result<bool, &'static str> {
+            if remaining == 0 {
+                return Err("Exhausted");
+            }
+            remaining -= 1;
+            Ok(true)
+        }),
+    );
+    assert_eq!(result.into_result(), Err("Exhausted"));
+}
+
+#[iex]
+fn consume<T, E>(f: impl IexFnOnce<(String, usize), E, Output = T>) -> Result<T, E> {
+    f.iex_call_once(("hello".to_string(), 3))
+}
+
+#[test]
+fn fn_once() {
+    let suffix = "!".to_string();
+    assert_eq!(
+        consume(move |s: String, n: usize| Ok::<_, ()>(s[..n].to_string() + &suffix)).into_result(),
+        Ok("hel!".to_string())
+    );
+    assert_eq!(
+        consume(|_: String, n: usize| Err::<(), _>(n)).into_result(),
+        Err(3)
+    );
+}
@@ -0,0 +1,87 @@
+use iex::{iex, Outcome};
+use std::fmt::Display;
+
+#[iex]
+fn digits<'a>(s: &'a str) -> Result<impl Iterator<Item = u8> + 'a, String> {
+    if let Some(c) = s.bytes().find(|c| !c.is_ascii_digit()) {
+        return Err(format!("Not a digit: {}", c as char));
+    }
+    Ok(s.bytes().map(|c| c - b'0'))
+}
+
+#[iex]
+fn digit_sum(s: &str) -> Result<u32, String> {
+    Ok(digits(s)?.map(u32::from).sum())
+}
+
+#[test]
+fn ok_position() {
+    assert_eq!(digit_sum("123").into_result(), Ok(6));
+    assert_eq!(
+        digit_sum("1x3").into_result(),
+        Err("Not a digit: x".to_string())
+    );
+}
+
+#[iex]
+fn checked_half(x: u32) -> Result<u32, impl Display> {
+    if x % 2 == 1 {
+        Err(format!("{x} is odd"))
+    } else {
+        Ok(x / 2)
+    }
+}
+
+#[test]
+fn err_position() {
+    assert_eq!(checked_half(4).into_result().ok(), Some(2));
+    assert_eq!(
+        checked_half(3).into_result().map_err(|err| err.to_string()),
+        Err("3 is odd".to_string())
+    );
+}
+
+#[iex]
+fn adder(s: &str) -> std::io::Result<impl Fn(u32) -> u32> {
+    let x: u32 = s
+        .parse()
+        .map_err(|_| std::io::Error::other("Invalid number"))?;
+    Ok(move |y| x + y)
+}
+
+#[test]
+fn alias() {
+    assert_eq!(adder("1").into_result().map(|add| add(2)).ok(), Some(3));
+    assert!(adder("x").into_result().is_err());
+}
+
+#[iex]
+trait Tokenizer {
+    fn tokens<'a>(&self, s: &'a str) -> Result<impl Iterator<Item = &'a str>, String>;
+}
+
+struct Whitespace;
+
+#[iex]
+impl Tokenizer for Whitespace {
+    fn tokens<'a>(&self, s: &'a str) -> Result<impl Iterator<Item = &'a str>, String> {
+        if s.is_empty() {
+            return Err("Empty input".to_string());
+        }
+        Ok(s.split_whitespace())
+    }
+}
+
+#[iex]
+fn count_tokens(tokenizer: &impl Tokenizer, s: &str) -> Result<usize, String> {
+    Ok(tokenizer.tokens(s)?.count())
+}
+
+#[test]
+fn traits() {
+    assert_eq!(count_tokens(&Whitespace, "a b  c").into_result(), Ok(3));
+    assert_eq!(
+        count_tokens(&Whitespace, "").into_result(),
+        Err("Empty input".to_string())
+    );
+}
@@ -0,0 +1,83 @@
+use iex::{iex, IntoOutcome, Outcome};
+
+#[derive(Debug, PartialEq)]
+enum Lookup<T> {
+    Found(T),
+    Missing(&'static str),
+}
+
+impl<T> IntoOutcome for Lookup<T> {
+    type IntoOutcome = Result<T, String>;
+
+    fn into_outcome(self) -> Self::IntoOutcome {
+        match self {
+            Lookup::Found(value) => Ok(value),
+            Lookup::Missing(key) => Err(format!("Missing key: {key}")),
+        }
+    }
+}
+
+fn lookup(key: &'static str) -> Lookup<i32> {
+    match key {
+        "one" => Lookup::Found(1),
+        "two" => Lookup::Found(2),
+        _ => Lookup::Missing(key),
+    }
+}
+
+#[iex]
+fn sum(a: &'static str, b: &'static str) -> Lookup<i32> {
+    if a == b {
+        return lookup(a);
+    }
+    Lookup::Found(lookup(a)? + lookup(b)?)
+}
+
+#[iex]
+fn sum_as_result(a: &'static str, b: &'static str) -> Result<i32, String> {
+    Ok(sum(a, b)? + lookup("one")?)
+}
+
+#[test]
+fn declared_return_type() {
+    assert_eq!(sum("one", "two").into_result(), Ok(3));
+    assert_eq!(sum("one", "one").into_result(), Ok(1));
+    assert_eq!(
+        sum("one", "three").into_result(),
+        Err("Missing key: three".to_string())
+    );
+}
+
+#[test]
+fn question_mark() {
+    assert_eq!(sum_as_result("one", "two").into_result(), Ok(4));
+    assert_eq!(
+        sum_as_result("four", "two").into_result(),
+        Err("Missing key: four".to_string())
+    );
+}
+
+// Wraps an #[iex] Result, so that conversion is lazy
+struct Deferred<F>(F);
+
+impl<R: Outcome, F: FnOnce() -> R> IntoOutcome for Deferred<F> {
+    type IntoOutcome = R;
+
+    fn into_outcome(self) -> R {
+        (self.0)()
+    }
+}
+
+#[iex]
+fn deferred(key: &'static str) -> Result<i32, usize> {
+    Deferred(move || sum(key, key)).map_err(|err| err.len())?;
+    Ok(0)
+}
+
+#[test]
+fn wrapped_outcome() {
+    assert_eq!(deferred("one").into_result(), Ok(0));
+    assert_eq!(deferred("three").into_result(), Err(18));
+    assert_eq!(lookup("two").unwrap_or(0), 2);
+    assert_eq!(lookup("three").ok(), None);
+}
@@ -23,6 +23,20 @@ fn elided_input_lifetime_struct(_a: A<'_>) -> Result<(), ()> {
     Ok(())
 }
 
+#[iex]
+fn elided_lifetime_struct_and_ref(_a: A<'_>, b: &u32) -> Result<u32, ()> {
+    Ok(*b)
+}
+
+struct Holder;
+
+#[iex]
+impl Holder {
+    fn elided_lifetime_method(&self, _a: A<'_>) -> Result<u32, ()> {
+        Ok(1)
+    }
+}
+
 #[iex]
 fn max_length<'a>(a: &'a str, b: &'a str) -> Result<&'a str, &'static str> {
     if a.len() > b.len() {
@@ -49,6 +63,14 @@ fn lifetimes() {
         elided_input_lifetime_struct(A(PhantomData)).into_result(),
         Ok(())
     );
+    assert_eq!(
+        elided_lifetime_struct_and_ref(A(PhantomData), &1).into_result(),
+        Ok(1)
+    );
+    assert_eq!(
+        Holder.elided_lifetime_method(A(PhantomData)).into_result(),
+        Ok(1)
+    );
     assert_eq!(max_length("Hello, ", "world!").into_result(), Ok("Hello, "));
 }
 
@@ -32,3 +32,46 @@ fn methods() {
     assert_eq!(A.ref_method().into_result(), Ok(&A));
     assert_eq!(A.mut_method().into_result(), Ok(&mut A));
 }
+
+struct Ref<'a, T>(Option<&'a T>);
+
+impl<'a, T: Clone> Ref<'a, T> {
+    #[iex(captures = "'a")]
+    fn get(self) -> Result<T, &'static str> {
+        self.0.cloned().ok_or("Empty")
+    }
+}
+
+struct Pair<'a, 'b>(&'a str, &'b str);
+
+#[iex]
+impl<'a, 'b> Pair<'a, 'b> {
+    fn longer(&self) -> Result<&'a str, &'static str> {
+        if self.0.len() >= self.1.len() {
+            Ok(self.0)
+        } else {
+            Err("Second is longer")
+        }
+    }
+
+    fn concat(self, separator: &str) -> Result<String, &'static str> {
+        Ok(format!("{}{separator}{}", self.0, self.1))
+    }
+
+    #[iex]
+    fn first(self) -> Result<usize, &'static str> {
+        Ok(self.0.len())
+    }
+}
+
+#[test]
+fn impl_lifetimes() {
+    assert_eq!(Ref(Some(&1)).get().into_result(), Ok(1));
+    assert_eq!(Ref::<i32>(None).get().into_result(), Err("Empty"));
+    assert_eq!(Pair("ab", "c").longer().into_result(), Ok("ab"));
+    assert_eq!(
+        Pair("a", "bc").concat(", ").into_result(),
+        Ok("a, bc".to_string())
+    );
+    assert_eq!(Pair("ab", "c").first().into_result(), Ok(2));
+}
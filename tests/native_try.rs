@@ -0,0 +1,69 @@
+#![cfg(feature = "nightly")]
+#![feature(try_blocks)]
+
+use iex::{iex, Outcome};
+
+#[iex]
+fn parse(s: &str) -> Result<i32, String> {
+    s.parse().map_err(|_| format!("Invalid number: {s}"))
+}
+
+fn plain_sum(a: &str, b: &str) -> Result<i32, String> {
+    Ok(parse(a)? + parse(b)?)
+}
+
+#[test]
+fn plain_function() {
+    assert_eq!(plain_sum("1", "2"), Ok(3));
+    assert_eq!(plain_sum("1", "x"), Err("Invalid number: x".to_string()));
+}
+
+#[derive(Debug, PartialEq)]
+struct Error(String);
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Self(message)
+    }
+}
+
+fn plain_converted(s: &str) -> Result<i32, Error> {
+    Ok(parse(s).map_err(|err| format!("Cannot parse: {err}"))?)
+}
+
+#[test]
+fn conversion() {
+    assert_eq!(plain_converted("1"), Ok(1));
+    assert_eq!(
+        plain_converted("x"),
+        Err(Error("Cannot parse: Invalid number: x".to_string()))
+    );
+}
+
+macro_rules! parse_twice {
+    ($s:expr) => {
+        parse($s)? * 2
+    };
+}
+
+#[iex]
+fn foreign_macro(s: &str) -> Result<i32, String> {
+    Ok(parse_twice!(s))
+}
+
+#[test]
+fn macro_generated() {
+    assert_eq!(foreign_macro("2").into_result(), Ok(4));
+    assert_eq!(
+        foreign_macro("x").into_result(),
+        Err("Invalid number: x".to_string())
+    );
+}
+
+#[test]
+fn try_block() {
+    let result: Result<i32, String> = try { parse("1")? + parse("2")? };
+    assert_eq!(result, Ok(3));
+    let result: Result<i32, String> = try { parse("1")? + parse("x")? };
+    assert_eq!(result, Err("Invalid number: x".to_string()));
+}
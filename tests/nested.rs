@@ -0,0 +1,103 @@
+use iex::{iex, Outcome};
+use std::cell::Cell;
+
+#[iex]
+fn fails(s: &str) -> Result<(), String> {
+    Err(s.to_string())
+}
+
+thread_local! {
+    static INNER: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+// Throws and catches an unrelated error while dropped, i.e. during propagation of an outer error
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let inner = fails("inner").into_result();
+        INNER.with(|cell| cell.set(Some(inner == Err("inner".to_string()))));
+    }
+}
+
+#[iex]
+fn fails_with_guard() -> Result<(), String> {
+    let _guard = Guard;
+    fails("outer")?;
+    Ok(())
+}
+
+#[test]
+fn drop_during_propagation() {
+    assert_eq!(fails_with_guard().into_result(), Err("outer".to_string()));
+    assert_eq!(INNER.with(Cell::get), Some(true));
+}
+
+#[iex]
+fn fails_with_other_type() -> Result<(), Vec<u64>> {
+    Err(vec![1, 2, 3])
+}
+
+#[iex]
+fn maps_err_with_nested_error() -> Result<(), String> {
+    fails("outer").map_err(|e| {
+        let inner = fails_with_other_type().into_result().unwrap_err();
+        format!("{e} {inner:?}")
+    })
+}
+
+#[test]
+fn map_err_nested() {
+    assert_eq!(
+        maps_err_with_nested_error().into_result(),
+        Err("outer [1, 2, 3]".to_string())
+    );
+}
+
+#[iex]
+fn inspects_err_with_nested_error() -> Result<(), String> {
+    fails("outer").inspect_err(|_| {
+        assert_eq!(fails("inner").into_result(), Err("inner".to_string()));
+    })
+}
+
+#[test]
+fn inspect_err_nested() {
+    assert_eq!(
+        inspects_err_with_nested_error().into_result(),
+        Err("outer".to_string())
+    );
+}
+
+#[test]
+fn foreign_panic_keeps_stack_balanced() {
+    #[iex]
+    fn panics() -> Result<(), String> {
+        let _guard = Guard;
+        panic!("foreign");
+    }
+
+    assert!(std::panic::catch_unwind(|| panics().into_result()).is_err());
+    assert_eq!(fails("after").into_result(), Err("after".to_string()));
+}
+
+// In checked builds, the swallowed error is reported with a panic instead
+#[test]
+#[cfg(not(any(debug_assertions, feature = "checked")))]
+fn swallowed_error_in_region() {
+    use iex::raw;
+    let result = raw::outcome(|marker: raw::Marker<String>| unsafe {
+        raw::map_exception(
+            marker,
+            |marker: raw::Marker<[u64; 3]>| {
+                let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    raw::forward(Err::<(), _>([1, 2, 3]), marker)
+                }));
+            },
+            |error| format!("{error:?}"),
+        );
+        raw::forward(Err::<(), _>("real".to_string()), marker)
+    })
+    .into_result();
+    assert_eq!(result, Err("real".to_string()));
+}
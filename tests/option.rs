@@ -0,0 +1,90 @@
+use iex::{iex, NoneError, Outcome};
+
+struct Trie {
+    value: Option<i32>,
+    children: Vec<(char, Trie)>,
+}
+
+impl Trie {
+    #[iex]
+    fn child<'a>(&'a self, c: char) -> Option<&'a Trie> {
+        self.children
+            .iter()
+            .find(|(key, _)| *key == c)
+            .map(|(_, child)| child)
+    }
+
+    #[iex]
+    fn get(&self, key: &str) -> Option<i32> {
+        let mut node = self;
+        for c in key.chars() {
+            node = node.child(c)?;
+        }
+        node.value
+    }
+}
+
+fn trie() -> Trie {
+    Trie {
+        value: None,
+        children: vec![(
+            'a',
+            Trie {
+                value: Some(1),
+                children: vec![(
+                    'b',
+                    Trie {
+                        value: Some(2),
+                        children: Vec::new(),
+                    },
+                )],
+            },
+        )],
+    }
+}
+
+#[test]
+fn lookup() {
+    let trie = trie();
+    assert_eq!(trie.get("a").into_option(), Some(1));
+    assert_eq!(trie.get("ab").into_option(), Some(2));
+    assert_eq!(trie.get("").into_option(), None);
+    assert_eq!(trie.get("b").into_option(), None);
+    assert_eq!(trie.get("abc").into_option(), None);
+}
+
+#[iex]
+fn first_even(values: &[i32]) -> Option<i32> {
+    for &value in values {
+        if value % 2 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[iex]
+fn halve_first_even(values: &[i32]) -> Option<i32> {
+    Some(first_even(values)? / 2)
+}
+
+#[test]
+fn early_return() {
+    assert_eq!(halve_first_even(&[1, 4, 6]).into_option(), Some(2));
+    assert_eq!(halve_first_even(&[1, 3]).into_option(), None);
+    assert_eq!(halve_first_even(&[1, 3]).into_result(), Err(NoneError));
+}
+
+#[iex]
+fn to_result(values: &[i32]) -> Result<i32, String> {
+    first_even(values).map_err(|NoneError| "No even values".to_string())
+}
+
+#[test]
+fn map_err() {
+    assert_eq!(to_result(&[2]).into_result(), Ok(2));
+    assert_eq!(
+        to_result(&[1]).into_result(),
+        Err("No even values".to_string())
+    );
+}
@@ -0,0 +1,79 @@
+use iex::{iex, raw, Outcome};
+
+fn parse(s: &str) -> impl Outcome<Output = i32, Error = String> + '_ {
+    raw::outcome(move |marker| unsafe {
+        raw::forward(
+            s.parse::<i32>().map_err(|_| format!("Invalid number: {s}")),
+            marker,
+        )
+    })
+}
+
+fn sum<'a>(values: &'a [&'a str]) -> impl Outcome<Output = i32, Error = String> + 'a {
+    raw::outcome(move |marker| {
+        let mut sum = 0;
+        for value in values {
+            sum += unsafe { raw::forward(parse(value), marker) };
+        }
+        sum
+    })
+}
+
+#[test]
+fn forward() {
+    assert_eq!(sum(&["1", "2", "3"]).into_result(), Ok(6));
+    assert_eq!(
+        sum(&["1", "x", "3"]).into_result(),
+        Err("Invalid number: x".to_string())
+    );
+}
+
+fn sum_or_len<'a>(values: &'a [&'a str]) -> impl Outcome<Output = i32, Error = usize> + 'a {
+    raw::outcome(move |marker| unsafe {
+        raw::map_exception(
+            marker,
+            |marker| raw::forward(sum(values), marker),
+            |err: String| err.len(),
+        )
+    })
+}
+
+#[test]
+fn map_exception() {
+    assert_eq!(sum_or_len(&["1", "2"]).into_result(), Ok(3));
+    assert_eq!(sum_or_len(&["abc"]).into_result(), Err(19));
+}
+
+#[iex]
+fn interop(values: &[&str]) -> Result<i32, String> {
+    Ok(sum(values)? * 2)
+}
+
+#[test]
+fn macro_interop() {
+    assert_eq!(interop(&["1", "2"]).into_result(), Ok(6));
+    assert_eq!(
+        interop(&["y"]).into_result(),
+        Err("Invalid number: y".to_string())
+    );
+    assert_eq!(sum(&["1"]).map(|x| x + 1).into_result(), Ok(2));
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "checked"))]
+fn forward_location() {
+    let payload = raw::outcome(|marker| {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+            raw::forward(Err::<(), i32>(1), marker)
+        }))
+        .unwrap_err()
+    })
+    .into_result()
+    .unwrap();
+    let info = payload
+        .downcast_ref::<iex::IexPanic>()
+        .unwrap()
+        .info()
+        .unwrap();
+    assert_eq!(info.location().unwrap().file(), file!());
+}
@@ -0,0 +1,150 @@
+use iex::{iex, Outcome};
+
+#[iex]
+fn parse(s: &str) -> Result<i32, String> {
+    s.parse().map_err(|_| format!("Invalid number: {s}"))
+}
+
+#[iex]
+fn dispatch(command: &str, arg: &str) -> Result<i32, String> {
+    if command == "parse" {
+        return parse(arg);
+    }
+    if command == "fail" {
+        return Err(format!("Failed on {arg}"));
+    }
+    match command {
+        "negate" => Ok(-parse(arg)?),
+        "double" => parse(arg).map_err(|err| format!("Cannot double: {err}")),
+        _ => Err("Unknown command".to_string()),
+    }
+}
+
+#[test]
+fn mixed_returns() {
+    assert_eq!(dispatch("parse", "12").into_result(), Ok(12));
+    assert_eq!(
+        dispatch("parse", "x").into_result(),
+        Err("Invalid number: x".to_string())
+    );
+    assert_eq!(
+        dispatch("fail", "x").into_result(),
+        Err("Failed on x".to_string())
+    );
+    assert_eq!(dispatch("negate", "12").into_result(), Ok(-12));
+    assert_eq!(
+        dispatch("double", "x").into_result(),
+        Err("Cannot double: Invalid number: x".to_string())
+    );
+    assert_eq!(
+        dispatch("halve", "12").into_result(),
+        Err("Unknown command".to_string())
+    );
+}
+
+#[iex]
+fn first_number(words: &[&str]) -> Result<i32, String> {
+    // `return` in nested closures and functions is left alone
+    let is_number = |word: &str| -> bool {
+        if word.is_empty() {
+            return false;
+        }
+        word.bytes().all(|c| c.is_ascii_digit())
+    };
+    fn fallback(default: i32) -> i32 {
+        if default < 0 {
+            return -1;
+        }
+        default
+    }
+    let mut i = 0;
+    let word = loop {
+        if i == words.len() {
+            return Ok(fallback(-2));
+        }
+        if is_number(words[i]) {
+            break words[i];
+        }
+        i += 1;
+    };
+    parse(word)
+}
+
+#[test]
+fn nested_returns() {
+    assert_eq!(first_number(&["a", "", "12", "3"]).into_result(), Ok(12));
+    assert_eq!(first_number(&["a"]).into_result(), Ok(-1));
+}
+
+#[iex]
+fn unfinished(x: i32) -> Result<i32, String> {
+    if x > 0 {
+        return parse("1");
+    }
+    todo!()
+}
+
+#[iex]
+fn forever() -> Result<i32, String> {
+    loop {
+        std::thread::park();
+    }
+}
+
+#[test]
+fn diverging() {
+    assert_eq!(unfinished(1).into_result(), Ok(1));
+    let _ = forever;
+}
+
+#[test]
+fn closures() {
+    let parse_or_zero = iex::closure!(|s: &str| -> Result<i32, String> {
+        if s.is_empty() {
+            return Ok(0);
+        }
+        parse(s)
+    });
+    assert_eq!(parse_or_zero.call(("",)).into_result(), Ok(0));
+    assert_eq!(parse_or_zero.call(("7",)).into_result(), Ok(7));
+}
+
+fn fail(message: &str) -> ! {
+    panic!("{message}")
+}
+
+#[iex]
+fn checked_parse(s: &str) -> Result<i32, String> {
+    if s.is_empty() {
+        return Err("Empty".to_string());
+    }
+    if let Some(s) = s.strip_prefix('!') {
+        return parse(s);
+    }
+    fail("Missing '!'")
+}
+
+macro_rules! bail {
+    ($message:expr) => {
+        return Err($message.to_string())
+    };
+}
+
+#[iex]
+fn reciprocal(s: &str) -> Result<i32, String> {
+    match parse(s)? {
+        0 => bail!("Zero"),
+        1 => parse(s),
+        _ => Ok(0),
+    }
+}
+
+#[test]
+fn diverging_calls() {
+    assert_eq!(checked_parse("").into_result(), Err("Empty".to_string()));
+    assert_eq!(checked_parse("!3").into_result(), Ok(3));
+    assert!(std::panic::catch_unwind(|| checked_parse("3").into_result()).is_err());
+    assert_eq!(reciprocal("0").into_result(), Err("Zero".to_string()));
+    assert_eq!(reciprocal("1").into_result(), Ok(1));
+    assert_eq!(reciprocal("2").into_result(), Ok(0));
+}
@@ -0,0 +1,111 @@
+use iex::{
+    iex,
+    sync::{Mutex, RwLock},
+    Outcome,
+};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+#[iex]
+fn fails() -> Result<(), String> {
+    Err("error".to_string())
+}
+
+#[iex]
+fn fails_while_locked(mutex: &Mutex<i32>) -> Result<(), String> {
+    let mut value = mutex.lock().unwrap();
+    *value += 1;
+    fails()?;
+    *value += 1;
+    Ok(())
+}
+
+#[test]
+fn mutex_not_poisoned_by_error() {
+    let mutex = Mutex::new(0);
+    assert_eq!(
+        fails_while_locked(&mutex).into_result(),
+        Err("error".to_string())
+    );
+    assert!(!mutex.is_poisoned());
+    assert_eq!(*mutex.lock().unwrap(), 1);
+}
+
+#[test]
+#[cfg(not(any(debug_assertions, feature = "checked")))]
+fn mutex_not_poisoned_after_swallowed_error() {
+    // Checked builds panic on the next throw instead of discarding the swallowed error
+    let _ = iex::raw::outcome(|marker| {
+        let _ = catch_unwind(AssertUnwindSafe(|| unsafe {
+            iex::raw::forward(fails(), marker)
+        }));
+    })
+    .into_result();
+    let mutex = Mutex::new(0);
+    assert!(fails_while_locked(&mutex).into_result().is_err());
+    assert!(!mutex.is_poisoned());
+}
+
+#[test]
+fn mutex_poisoned_by_panic() {
+    let mutex = Mutex::new(0);
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let _guard = mutex.lock().unwrap();
+        panic!("foreign");
+    }));
+    assert!(mutex.is_poisoned());
+    assert!(mutex.lock().is_err());
+    mutex.clear_poison();
+    assert_eq!(mutex.into_inner().unwrap(), 0);
+}
+
+#[iex]
+fn fails_while_write_locked(lock: &RwLock<Vec<i32>>) -> Result<(), String> {
+    let mut values = lock.write().unwrap();
+    values.push(1);
+    fails()?;
+    values.push(2);
+    Ok(())
+}
+
+#[test]
+fn rwlock_not_poisoned_by_error() {
+    let lock = RwLock::new(Vec::new());
+    assert!(fails_while_write_locked(&lock).into_result().is_err());
+    assert!(!lock.is_poisoned());
+    assert_eq!(*lock.read().unwrap(), [1]);
+    assert!(lock.try_write().is_ok());
+}
+
+#[test]
+fn rwlock_poisoned_by_panic() {
+    let lock = RwLock::new(0);
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let _guard = lock.write().unwrap();
+        panic!("foreign");
+    }));
+    assert!(lock.is_poisoned());
+    assert!(lock.read().is_err());
+}
+
+#[test]
+fn locked_during_propagation() {
+    // Locks acquired and released in destructors while an error is propagated are not poisoned
+    struct LocksOnDrop<'a>(&'a Mutex<i32>);
+
+    impl Drop for LocksOnDrop<'_> {
+        fn drop(&mut self) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    #[iex]
+    fn fails_with_guard(mutex: &Mutex<i32>) -> Result<(), String> {
+        let _guard = LocksOnDrop(mutex);
+        fails()
+    }
+
+    let mutex = Mutex::new(0);
+    assert!(fails_with_guard(&mutex).into_result().is_err());
+    assert!(!mutex.is_poisoned());
+    assert_eq!(*mutex.lock().unwrap(), 1);
+}
@@ -59,3 +59,67 @@ fn refined() {
     );
     assert_eq!("test".say_hello().into_result().unwrap(), "test");
 }
+
+trait Decoder {
+    #[iex(object_safe)]
+    fn decode(&self, input: &[u8]) -> Result<u32, String>;
+
+    #[iex(object_safe)]
+    fn feed(&mut self, input: &[u8]) -> Result<(), String> {
+        self.decode(input)?;
+        Ok(())
+    }
+}
+
+struct Digit;
+
+impl Decoder for Digit {
+    #[iex(object_safe)]
+    fn decode(&self, input: &[u8]) -> Result<u32, String> {
+        match input {
+            [c @ b'0'..=b'9'] => Ok((c - b'0') as u32),
+            _ => Err(format!("Not a digit: {input:?}")),
+        }
+    }
+}
+
+struct Sum(u32);
+
+impl Decoder for Sum {
+    #[iex(object_safe)]
+    fn decode(&self, input: &[u8]) -> Result<u32, String> {
+        let mut sum = self.0;
+        for c in input {
+            sum += Digit.decode(std::slice::from_ref(c))?;
+        }
+        Ok(sum)
+    }
+
+    #[iex(object_safe)]
+    fn feed(&mut self, input: &[u8]) -> Result<(), String> {
+        self.0 = self.decode(input)?;
+        Ok(())
+    }
+}
+
+#[iex]
+fn decode_all(decoders: &mut [Box<dyn Decoder>], input: &[u8]) -> Result<u32, String> {
+    let mut total = 0;
+    for decoder in decoders {
+        decoder.feed(input)?;
+        total += decoder.decode(input)?;
+    }
+    Ok(total)
+}
+
+#[test]
+fn dyn_dispatch() {
+    let mut decoders: Vec<Box<dyn Decoder>> = vec![Box::new(Sum(0)), Box::new(Digit)];
+    assert_eq!(decoders[1].decode(b"7").into_result(), Ok(7));
+    assert_eq!(decode_all(&mut decoders[..1], b"12").into_result(), Ok(6));
+    assert_eq!(
+        decode_all(&mut decoders, b"12").into_result(),
+        Err("Not a digit: [49, 50]".to_string()),
+    );
+    assert_eq!(Sum(1).decode(b"23").into_result(), Ok(6));
+}
@@ -3,12 +3,14 @@
 //!
 
 use iex::Outcome;
+use std::future::Future;
 
 #[iex::iex]
 fn func1() -> Result<u32, &'static str> {
     Ok(1)
 }
 
+#[iex::try_macro]
 macro_rules! test {
     () => {
         func1()?
@@ -31,5 +33,139 @@ fn test_nested_functions() {
     assert_eq!(res, Ok(1));
 
     let res = func3(3).into_result();
-    assert_eq!(res, Ok(2));
+    assert_eq!(res, Ok(1));
+}
+
+#[iex::iex]
+fn parse(s: &str) -> Result<u32, String> {
+    s.parse().map_err(|_| format!("Invalid number {s:?}"))
+}
+
+#[iex::try_macro]
+macro_rules! parse_next {
+    ($iter:expr) => {
+        parse($iter.next().ok_or("Unexpected end of input")?)?
+    };
+}
+
+#[iex::iex]
+fn sum_pair(s: &str) -> Result<u32, String> {
+    let mut iter = s.split(',');
+    Ok(parse_next!(iter) + parse_next!(iter))
+}
+
+#[iex::try_macro]
+macro_rules! first {
+    ($s:expr) => {
+        $s.chars().next().ok_or("Empty string")?
+    };
+}
+
+fn first_char(s: &str) -> Result<char, &'static str> {
+    // Outside #[iex] functions, this is the built-in `?`
+    Ok(first!(s))
+}
+
+#[test]
+fn try_macro() {
+    assert_eq!(sum_pair("1,2").into_result(), Ok(3));
+    assert_eq!(
+        sum_pair("1").into_result(),
+        Err("Unexpected end of input".to_string())
+    );
+    assert_eq!(
+        sum_pair("1,x").into_result(),
+        Err("Invalid number \"x\"".to_string())
+    );
+    assert_eq!(first_char("abc"), Ok('a'));
+    assert_eq!(first_char(""), Err("Empty string"));
+}
+
+#[iex::iex]
+fn in_arguments(s: &str) -> Result<Vec<u32>, String> {
+    let numbers = vec![parse(s)?, s.parse::<u32>().map_err(|e| e.to_string())? * 2];
+    assert_eq!(format!("{:?}", parse(s)?), s);
+    Ok(numbers)
+}
+
+#[iex::iex]
+fn in_local_macro(s: &str) -> Result<u32, String> {
+    #[iex::try_macro]
+    macro_rules! twice {
+        ($s:expr) => {
+            parse($s)? * 2
+        };
+    }
+    Ok(twice!(s))
+}
+
+#[test]
+fn best_effort() {
+    assert_eq!(in_arguments("2").into_result(), Ok(vec![2, 4]));
+    assert_eq!(
+        in_arguments("x").into_result(),
+        Err("Invalid number \"x\"".to_string())
+    );
+    assert_eq!(in_local_macro("2").into_result(), Ok(4));
+    assert_eq!(
+        in_local_macro("x").into_result(),
+        Err("Invalid number \"x\"".to_string())
+    );
+}
+
+#[iex::iex]
+fn in_closure_argument(v: &[&str]) -> Result<Vec<Result<u32, std::num::ParseIntError>>, String> {
+    let parsed = vec![v
+        .iter()
+        .map(|s| -> Result<u32, _> { Ok(s.parse::<u32>()? + 1) })
+        .collect::<Vec<_>>()];
+    let _ = parse(v[0])?;
+    Ok(parsed.into_iter().next().unwrap())
+}
+
+#[iex::iex]
+fn in_async_block(s: &str) -> Result<Result<bool, String>, String> {
+    let mut futures = vec![async { Ok(s.parse::<u32>().map_err(|e| e.to_string())? == 1) }];
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    match std::pin::pin!(futures.remove(0)).poll(&mut cx) {
+        std::task::Poll::Ready(result) => Ok(result),
+        std::task::Poll::Pending => unreachable!(),
+    }
+}
+
+#[test]
+fn closures_keep_their_own_try() {
+    let result = in_closure_argument(&["1", "x", "3"]).into_result().unwrap();
+    assert_eq!(result[0], Ok(2));
+    assert!(result[1].is_err());
+    assert_eq!(result[2], Ok(4));
+    assert!(in_async_block("x").into_result().unwrap().is_err());
+    assert_eq!(in_async_block("1").into_result(), Ok(Ok(true)));
+}
+
+macro_rules! tokens {
+    ($($tokens:tt)*) => {
+        stringify!($($tokens)*)
+    };
+}
+
+#[iex::iex]
+fn in_other_macros() -> Result<Vec<String>, String> {
+    Ok(vec![
+        tokens!(value?).to_string(),
+        stringify!(parse("1")?).to_string(),
+        format!("{}", tokens!(a.b()?)),
+    ])
+}
+
+#[test]
+fn other_macros_left_alone() {
+    assert_eq!(
+        in_other_macros().into_result(),
+        Ok(vec![
+            "value?".to_string(),
+            "parse(\"1\")?".to_string(),
+            "a.b()?".to_string(),
+        ])
+    );
 }
@@ -0,0 +1,37 @@
+// The uncaught error handler is global, so this test runs in its own process.
+
+use iex::{iex, Outcome};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Mutex;
+
+#[derive(Debug)]
+struct LostError;
+
+#[iex]
+fn fails() -> Result<(), LostError> {
+    Err(LostError)
+}
+
+#[test]
+fn uncaught_at_thread_exit() {
+    static REPORTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    iex::set_uncaught_handler(Box::new(|info| {
+        REPORTED.lock().unwrap().push(info.type_name().to_string());
+    }));
+    std::thread::spawn(|| {
+        let _ = iex::raw::outcome(|marker| {
+            catch_unwind(AssertUnwindSafe(|| unsafe {
+                iex::raw::forward(fails(), marker)
+            }))
+        })
+        .into_result();
+    })
+    .join()
+    .unwrap();
+    assert!(iex::take_uncaught_handler().is_some());
+    assert!(REPORTED
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|name| name.ends_with("LostError")));
+}
@@ -0,0 +1,101 @@
+use iex::{iex, IexPanic, Outcome};
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+#[derive(Debug, PartialEq)]
+struct LostError;
+
+#[iex]
+fn fails() -> Result<(), LostError> {
+    Err(LostError)
+}
+
+#[test]
+fn catch_unwind_passes_errors() {
+    let result = iex::raw::outcome(|marker| {
+        iex::catch_unwind(|| unsafe { iex::raw::forward(fails(), marker) })
+    })
+    .into_result();
+    assert!(matches!(result, Err(LostError)));
+}
+
+#[test]
+fn catch_unwind_catches_panics() {
+    let payload = iex::catch_unwind(|| panic!("foreign")).unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"foreign"));
+}
+
+// Swallows an error with a foreign catch_unwind and returns the payload
+fn swallow() -> Box<dyn std::any::Any + Send> {
+    iex::raw::outcome(|marker| {
+        catch_unwind(AssertUnwindSafe(|| unsafe {
+            iex::raw::forward(fails(), marker)
+        }))
+        .unwrap_err()
+    })
+    .into_result()
+    .unwrap_or_else(|_: LostError| unreachable!())
+}
+
+#[iex]
+fn panics() -> Result<(), String> {
+    panic!("bug")
+}
+
+#[test]
+fn panic_after_swallowed_error() {
+    // The swallowed error must not be mistaken for an error thrown within map_err
+    drop(swallow());
+    let payload = catch_unwind(|| panics().map_err(|err| err.len()).into_result()).unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"bug"));
+}
+
+#[test]
+fn panic_after_leaked_error() {
+    // The leaked error looks like it is in flight, but its type doesn't match, so it is reported as
+    // lost instead of aborting the process
+    std::mem::forget(swallow());
+    let payload = catch_unwind(|| panics().map_err(|err| err.len()).into_result()).unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"bug"));
+}
+
+#[test]
+fn payload() {
+    std::thread::spawn(|| {
+        let payload = swallow();
+        let panic = payload.downcast_ref::<IexPanic>().unwrap();
+        let info = panic.info().unwrap();
+        assert!(info.type_name().ends_with("LostError"));
+        assert!(info.to_string().starts_with("iex error of type `"));
+        if cfg!(any(debug_assertions, feature = "checked")) {
+            assert_eq!(info.location().unwrap().file(), file!());
+        }
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn crossed_thread_boundary() {
+    let payload = std::thread::spawn(|| {
+        iex::raw::outcome(|marker| {
+            catch_unwind(AssertUnwindSafe(|| unsafe {
+                iex::raw::forward(fails(), marker)
+            }))
+            .unwrap_err()
+        })
+        .into_result()
+        .unwrap()
+    })
+    .join()
+    .unwrap();
+    let message = catch_unwind(AssertUnwindSafe(|| {
+        iex::raw::outcome(|_marker: iex::raw::Marker<LostError>| resume_unwind(payload))
+            .into_result()
+    }))
+    .unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(
+        message.contains("did its unwinding cross a thread boundary"),
+        "{message}"
+    );
+}